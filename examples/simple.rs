@@ -36,7 +36,7 @@ fn main() {
 
     // Put it all in a tree, print it, and run it
     let mut tree = BehaviorTree::new(root);
-    println!("{}", tree);
+    println!("{:#}", tree);
     let res = tree.run(4.0, &mut world_state, Some(hook));
 
     println!("\nTree finished: {:?}", res);
@@ -67,5 +67,5 @@ fn do_sub(state: &mut Arc<Mutex<WorldState>>) -> Status {
 
 // Display the tree after each tick
 fn hook<W>(tree: &BehaviorTree<W>) {
-    println!("{}", tree);
+    println!("{}", tree.pretty().color());
 }
@@ -0,0 +1,335 @@
+//! A typed key-value store that nodes can share instead of a monolithic world.
+//!
+//! A `Blackboard` maps names to values of any type. Values are accessed
+//! through a `Key<T>`, which pairs the name with the type of the value so that
+//! reads and writes are checked when they happen rather than by convention.
+//!
+//! The blackboard is organized as a stack of `Scope`s. The bottom scope belongs
+//! to the whole tree and each `SubTree` node pushes its own scope while its
+//! child is being ticked. A scope can *remap* a local name to a name in the
+//! enclosing scope, which lets a reusable subtree read `goal` while the parent
+//! stores the value under `target_pose`.
+//!
+//! Lookups fall through to the enclosing scopes if a name is not found in the
+//! current one. Writes go to the current scope unless the name is remapped, in
+//! which case they are forwarded to the enclosing scope under the remapped
+//! name.
+//!
+//! # Examples
+//!
+//! ```
+//! # use aspen::blackboard::{Blackboard, Key, Scope};
+//! let pose = Key::<(f64, f64)>::new("target_pose");
+//! let goal = Key::<(f64, f64)>::new("goal");
+//!
+//! let mut board = Blackboard::new();
+//! board.set(&pose, (1.0, 2.0));
+//!
+//! board.push_scope(Scope::new().remap("goal", "target_pose"));
+//! assert_eq!(board.get(&goal), Some(&(1.0, 2.0)));
+//! board.pop_scope();
+//!
+//! assert_eq!(board.get(&goal), None);
+//! ```
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A name paired with the type of the value stored under it.
+pub struct Key<T> {
+    /// The name of the entry.
+    name: String,
+
+    /// Marker for the type of the value.
+    _marker: PhantomData<fn() -> T>,
+}
+impl<T> Key<T> {
+    /// Creates a new key with the given name.
+    pub fn new<S: Into<String>>(name: S) -> Key<T> {
+        Key {
+            name: name.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the name of this key.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+impl<T> Clone for Key<T> {
+    fn clone(&self) -> Self {
+        Key::new(self.name.clone())
+    }
+}
+impl<T> fmt::Debug for Key<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Key({:?})", self.name)
+    }
+}
+
+/// A single layer of a `Blackboard`.
+///
+/// Scopes hold their own entries as well as the port remapping from local
+/// names to names in the enclosing scope.
+#[derive(Default)]
+pub struct Scope {
+    /// Entries that are local to this scope.
+    entries: HashMap<String, Box<dyn Any + Send>>,
+
+    /// Local names that are forwarded to the enclosing scope.
+    remap: HashMap<String, String>,
+}
+impl Scope {
+    /// Creates an empty scope without any remapping.
+    pub fn new() -> Scope {
+        Scope::default()
+    }
+
+    /// Forwards the local name `local` to `parent` in the enclosing scope.
+    pub fn remap<L, P>(mut self, local: L, parent: P) -> Scope
+    where
+        L: Into<String>,
+        P: Into<String>,
+    {
+        self.remap.insert(local.into(), parent.into());
+        self
+    }
+
+    /// Returns the remapping as pairs of local and parent names, sorted by the
+    /// local name.
+    pub fn remapping(&self) -> Vec<(&str, &str)> {
+        let mut remap: Vec<_> = self
+            .remap
+            .iter()
+            .map(|(l, p)| (l.as_str(), p.as_str()))
+            .collect();
+        remap.sort();
+        remap
+    }
+
+    /// Removes all of the local entries, keeping the remapping.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// A stack of scopes holding typed values.
+///
+/// See the module level documentation for how names are resolved.
+pub struct Blackboard {
+    /// The scopes, with the innermost scope last.
+    ///
+    /// This is never empty: the root scope cannot be popped.
+    scopes: Vec<Scope>,
+}
+impl Blackboard {
+    /// Creates an empty blackboard with a single root scope.
+    pub fn new() -> Blackboard {
+        Blackboard {
+            scopes: vec![Scope::new()],
+        }
+    }
+
+    /// Makes `scope` the innermost scope.
+    pub fn push_scope(&mut self, scope: Scope) {
+        self.scopes.push(scope);
+    }
+
+    /// Removes and returns the innermost scope.
+    ///
+    /// Returns `None` if only the root scope remains.
+    pub fn pop_scope(&mut self) -> Option<Scope> {
+        if self.scopes.len() > 1 {
+            self.scopes.pop()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of scopes, including the root scope.
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Gets a reference to the value stored under `key`.
+    ///
+    /// Returns `None` if there is no such entry or if it holds a value of a
+    /// different type.
+    pub fn get<T: Any>(&self, key: &Key<T>) -> Option<&T> {
+        let (level, name) = self.find(key.name())?;
+        self.scopes[level].entries[&name].downcast_ref()
+    }
+
+    /// Gets a mutable reference to the value stored under `key`.
+    pub fn get_mut<T: Any>(&mut self, key: &Key<T>) -> Option<&mut T> {
+        let (level, name) = self.find(key.name())?;
+        self.scopes[level]
+            .entries
+            .get_mut(&name)
+            .and_then(|v| v.downcast_mut())
+    }
+
+    /// Returns `true` if there is a value of the right type under `key`.
+    pub fn contains<T: Any>(&self, key: &Key<T>) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Stores `value` under `key`, returning the previous value if it had
+    /// the same type.
+    pub fn set<T: Any + Send>(&mut self, key: &Key<T>, value: T) -> Option<T> {
+        let (level, name) = self.resolve(self.scopes.len() - 1, key.name());
+        self.scopes[level]
+            .entries
+            .insert(name, Box::new(value))
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+
+    /// Removes the value stored under `key`, returning it if it had the right
+    /// type.
+    ///
+    /// Like `set`, this only affects the current scope or, through
+    /// remapping, one of the enclosing scopes.
+    pub fn remove<T: Any>(&mut self, key: &Key<T>) -> Option<T> {
+        let (level, name) = self.resolve(self.scopes.len() - 1, key.name());
+        self.scopes[level]
+            .entries
+            .remove(&name)
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+
+    /// Follows the remapping starting at the given scope level.
+    ///
+    /// Returns the level at which `name` is no longer remapped along with the
+    /// name at that level.
+    fn resolve(&self, mut level: usize, name: &str) -> (usize, String) {
+        let mut name = name.to_owned();
+        while level > 0 {
+            match self.scopes[level].remap.get(&name) {
+                Some(parent) => {
+                    name = parent.clone();
+                    level -= 1;
+                }
+                None => break,
+            }
+        }
+
+        (level, name)
+    }
+
+    /// Finds the scope level and name under which `name` is currently stored.
+    fn find(&self, name: &str) -> Option<(usize, String)> {
+        let mut level = self.scopes.len() - 1;
+        let mut name = name.to_owned();
+        loop {
+            let (l, n) = self.resolve(level, &name);
+            if self.scopes[l].entries.contains_key(&n) {
+                return Some((l, n));
+            } else if l == 0 {
+                return None;
+            }
+
+            // Fall through to the enclosing scope
+            level = l - 1;
+            name = n;
+        }
+    }
+}
+impl Default for Blackboard {
+    fn default() -> Self {
+        Blackboard::new()
+    }
+}
+
+/// A world type that contains a `Blackboard`.
+///
+/// Implementing this for the world allows the blackboard-aware node
+/// constructors (such as `Condition::with_key`) and `SubTree` to be used.
+pub trait HasBlackboard {
+    /// Returns a reference to the blackboard.
+    fn blackboard(&self) -> &Blackboard;
+
+    /// Returns a mutable reference to the blackboard.
+    fn blackboard_mut(&mut self) -> &mut Blackboard;
+}
+impl HasBlackboard for Blackboard {
+    fn blackboard(&self) -> &Blackboard {
+        self
+    }
+
+    fn blackboard_mut(&mut self) -> &mut Blackboard {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_access() {
+        let count = Key::<u32>::new("count");
+        let wrong = Key::<String>::new("count");
+
+        let mut board = Blackboard::new();
+        assert_eq!(board.set(&count, 1), None);
+        assert_eq!(board.set(&count, 2), Some(1));
+        assert_eq!(board.get(&count), Some(&2));
+        assert_eq!(board.get(&wrong), None);
+
+        *board.get_mut(&count).unwrap() += 1;
+        assert_eq!(board.remove(&count), Some(3));
+        assert!(!board.contains(&count));
+    }
+
+    #[test]
+    fn scopes_shadow_and_fall_through() {
+        let a = Key::<u32>::new("a");
+        let b = Key::<u32>::new("b");
+
+        let mut board = Blackboard::new();
+        board.set(&a, 1);
+        board.set(&b, 2);
+
+        board.push_scope(Scope::new());
+        board.set(&a, 10);
+        assert_eq!(board.get(&a), Some(&10));
+        assert_eq!(board.get(&b), Some(&2));
+
+        let scope = board.pop_scope().unwrap();
+        assert_eq!(board.get(&a), Some(&1));
+        assert!(board.pop_scope().is_none());
+
+        // Entries persist in the popped scope
+        board.push_scope(scope);
+        assert_eq!(board.get(&a), Some(&10));
+    }
+
+    #[test]
+    fn remapping() {
+        let pose = Key::<u32>::new("target_pose");
+        let goal = Key::<u32>::new("goal");
+
+        let mut board = Blackboard::new();
+        board.set(&pose, 5);
+
+        board.push_scope(Scope::new().remap("goal", "target_pose"));
+        board.push_scope(Scope::new());
+        assert_eq!(board.get(&goal), Some(&5));
+        assert_eq!(board.get(&pose), Some(&5));
+
+        // Writes from the innermost scope stay local
+        board.set(&goal, 6);
+        board.pop_scope();
+        assert_eq!(board.get(&goal), Some(&5));
+
+        // Writes through the remapping reach the parent
+        board.set(&goal, 7);
+        board.pop_scope();
+        assert_eq!(board.get(&pose), Some(&7));
+        assert_eq!(board.get(&goal), None);
+    }
+}
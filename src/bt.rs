@@ -1,19 +1,122 @@
+use std::convert::TryFrom;
 use std::fmt;
-use std::thread;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
-use crate::node::{Node, Tickable};
+use crate::clock::Clock;
+use crate::context::Context;
+use crate::executor::{ActionExecutor, PanicPolicy};
+use crate::format::NodeDesc;
+use crate::graph;
+use crate::node::{self, Node, NodeId, Tickable};
+use crate::observer::TreeObserver;
+use crate::pretty::Pretty;
+use crate::profile::Profile;
+use crate::runner::{OverrunPolicy, RunConfig, RunStats};
 use crate::status::Status;
+use std::future::{self, Future};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{self, Poll, Waker};
 
 /// Main behavior tree struct.
 pub struct BehaviorTree<'a, W> {
     /// Root node of the behavior tree.
     root: Node<'a, W>,
+
+    /// State shared by all of the nodes in the tree.
+    context: Rc<Context>,
+
+    /// The number of times the tree has been ticked.
+    ticks: u64,
+
+    /// The statistics of the current or last run.
+    run_stats: RunStats,
 }
 impl<'a, W> BehaviorTree<'a, W> {
     /// Create a new behavior tree with the supplied `Node` as the root.
-    pub fn new(root: Node<'a, W>) -> BehaviorTree<'a, W> {
-        BehaviorTree { root: root }
+    ///
+    /// This assigns every node in the tree its ID and path, which are used to
+    /// identify the node to observers.
+    pub fn new(mut root: Node<'a, W>) -> BehaviorTree<'a, W> {
+        let context = Rc::new(Context::default());
+        let path = node::path_segment(root.name());
+        root.attach_tree(&context, path, &mut 0);
+
+        BehaviorTree {
+            root,
+            context,
+            ticks: 0,
+            run_stats: RunStats::default(),
+        }
+    }
+
+    /// Adds an observer that will be notified of events from every node in
+    /// the tree.
+    pub fn add_observer<O>(&mut self, observer: O)
+    where
+        O: TreeObserver + 'static,
+    {
+        self.context.add_observer(Box::new(observer));
+    }
+
+    /// Sets the executor that runs the tasks of the tree's threaded `Action`
+    /// nodes.
+    ///
+    /// Actions that are already running are not affected. Without an executor,
+    /// every run of an action spawns its own thread.
+    pub fn set_executor(&mut self, executor: Arc<dyn ActionExecutor>) {
+        self.context.set_executor(executor);
+    }
+
+    /// Sets what the tree's threaded `Action` nodes do when their task panics.
+    ///
+    /// By default they fail.
+    pub fn set_panic_policy(&mut self, policy: PanicPolicy) {
+        self.context.set_panic_policy(policy);
+    }
+
+    /// Sets the clock that `run` schedules ticks with and that time-based
+    /// nodes in the tree measure time with.
+    ///
+    /// Nodes that are already measuring a duration may behave unexpectedly
+    /// if the clock is replaced, so this should be done before the tree is
+    /// first ticked. See the `clock` module.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.context.set_clock(clock);
+    }
+
+    /// Turns the collection of timing statistics for every node on or off.
+    ///
+    /// Statistics that have already been collected are kept when profiling
+    /// is turned off. See the `profile` module.
+    pub fn set_profiling(&mut self, profiling: bool) {
+        self.context.set_profiling(profiling);
+    }
+
+    /// Returns the timing statistics of every node that has been profiled.
+    pub fn profile(&self) -> Profile {
+        Profile::new(&self.root)
+    }
+
+    /// Forgets the timing statistics of every node.
+    pub fn clear_profile(&mut self) {
+        self.root.clear_stats();
+    }
+
+    /// Sets the waker that the futures of the tree's `AsyncAction` nodes are
+    /// polled with.
+    ///
+    /// The waker is woken when one of those futures can make progress, so a
+    /// loop that ticks the tree can sleep until then. Without a waker, the
+    /// futures are simply polled again on every tick.
+    pub fn set_waker(&mut self, waker: Waker) {
+        self.context.set_waker(Some(waker));
+    }
+
+    /// Returns the number of times the tree has been ticked.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
     }
 
     /// Returns a reference to the root node.
@@ -21,12 +124,73 @@ impl<'a, W> BehaviorTree<'a, W> {
         &self.root
     }
 
+    /// Returns the node with the given path, such as `root/Sequence/Action[1]`.
+    ///
+    /// See the `observer` module for the format of paths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use aspen::std_nodes::*;
+    /// # use aspen::BehaviorTree;
+    /// let root = Sequence::new(vec![
+    ///     AlwaysSucceed::new(),
+    ///     Invert::new(AlwaysFail::new()),
+    ///     AlwaysSucceed::new(),
+    /// ]);
+    /// let tree: BehaviorTree<()> = BehaviorTree::new(root.named(Some("root")));
+    ///
+    /// let node = tree.find("root/AlwaysSucceed[1]").unwrap();
+    /// assert_eq!(node.id().unwrap().index(), 4);
+    /// assert!(tree.find("root/Invert/AlwaysFail").is_some());
+    /// assert!(tree.find("root/AlwaysSucceed").is_none());
+    /// ```
+    pub fn find(&self, path: &str) -> Option<&Node<'_, W>> {
+        self.root.find(path)
+    }
+
+    /// Returns the node with the given ID.
+    pub fn node(&self, id: NodeId) -> Option<&Node<'_, W>> {
+        self.root.find_id(id)
+    }
+
+    /// Returns a description of the structure of the tree.
+    ///
+    /// See `Node::describe`.
+    pub fn describe(&self) -> NodeDesc {
+        self.root.describe()
+    }
+
+    /// Returns a printer that writes the tree with one node per line.
+    ///
+    /// The printer can be configured to use ASCII characters or ANSI colors
+    /// before it is displayed.
+    pub fn pretty(&self) -> Pretty<'_, 'a, W> {
+        Pretty::new(&self.root)
+    }
+
+    /// Renders the tree, colored by the status of each node, as a Graphviz
+    /// DOT digraph.
+    pub fn to_dot(&self) -> String {
+        graph::to_dot(&self.root)
+    }
+
+    /// Renders the tree, colored by the status of each node, as a Mermaid
+    /// flowchart.
+    pub fn to_mermaid(&self) -> String {
+        graph::to_mermaid(&self.root)
+    }
+
     /// Tick the behavior tree a single time.
     ///
     /// If the tree has already been completed, ticking it again will reset it.
     /// When the tree is reset, it will return an `Initialized` status a single
     /// time.
     pub fn tick(&mut self, world: &mut W) -> Status {
+        self.ticks += 1;
+        let tick = self.ticks;
+        self.context.notify(|o| o.on_tree_tick(tick));
+
         match self.root.status() {
             None | Some(Status::Running) => self.root.tick(world),
             Some(Status::Failed) | Some(Status::Succeeded) => {
@@ -52,67 +216,252 @@ impl<'a, W> BehaviorTree<'a, W> {
     /// If the hook is supplied, it will be run after every tick. A reference to this
     /// behavior tree will be supplied as an argument.
     ///
+    /// This is the same as `run_with` using `RunConfig::new(freq)`, which
+    /// starts the schedule over whenever a tick overruns.
+    ///
     /// NOTE: The only time this will return `Status::Running` is if the frequency is zero
     /// and the behavior tree is running after the first tick.
-    pub fn run<F>(&mut self, freq: f64, world: &mut W, mut hook: Option<F>) -> Status
+    pub fn run<F>(&mut self, freq: f64, world: &mut W, hook: Option<F>) -> Status
+    where
+        F: FnMut(&BehaviorTree<'a, W>),
+    {
+        self.run_with(RunConfig::new(freq), world, hook)
+    }
+
+    /// Run the behavior tree on the schedule given by the configuration
+    /// until it either succeeds or fails.
+    ///
+    /// If the hook is supplied, it will be run after every tick, and can read
+    /// the statistics of the run so far with `run_stats`. Every overrun is
+    /// logged as a warning and handled according to the `OverrunPolicy` of
+    /// the configuration. See the `runner` module.
+    pub fn run_with<F>(&mut self, config: RunConfig, world: &mut W, mut hook: Option<F>) -> Status
     where
         F: FnMut(&BehaviorTree<'a, W>),
     {
+        self.run_stats = RunStats::default();
+
         // Deal with the "special" case of a zero frequency
-        if freq == 0.0f64 {
-            debug!("Zero frequency specified, ticking once");
-            let status = self.tick(world);
+        let period = match config.period() {
+            Some(period) => period,
+            None => {
+                debug!("Zero frequency specified, ticking once");
+                self.run_stats.ticks = 1;
+                let status = self.tick(world);
+                if let Some(ref mut f) = hook {
+                    f(self);
+                }
+
+                return status;
+            }
+        };
+
+        // Now, run at the given frequency
+        let clock = self.context.clock();
+        let mut status = Status::Running;
+        let mut last_start = None;
+        let mut deadline = clock.now();
+        debug!("Ticking at {}Hz", config.freq());
+        while status == Status::Running {
+            let start = clock.now();
+            match last_start {
+                Some(last) => self.run_stats.record_period(start - last, period),
+                None => deadline = start,
+            }
+            last_start = Some(start);
+            self.run_stats.ticks += 1;
+
+            trace!("Ticking tree");
+            status = self.tick(world);
             if let Some(ref mut f) = hook {
                 f(self);
             }
 
-            return status;
+            let now = clock.now();
+            let elapsed = now - start;
+            self.run_stats.max_tick_time = self.run_stats.max_tick_time.max(elapsed);
+
+            // Specifying a frequency of infinity means running as fast as
+            // possible, so this thread never gets an opportunity to sleep
+            if status.is_done() || period == Duration::default() {
+                continue;
+            }
+
+            // A period too long for the clock is never over
+            deadline = match deadline.checked_add(period) {
+                Some(deadline) => deadline,
+                None => {
+                    clock.sleep(period - elapsed.min(period));
+                    continue;
+                }
+            };
+            if now <= deadline {
+                clock.sleep(deadline - now);
+                continue;
+            }
+
+            self.run_stats.overruns += 1;
+            warn!(
+                "Unable to tick at desired frequency: Expected {:?}, elapsed {:?}",
+                period, elapsed
+            );
+            match config.overrun_policy() {
+                OverrunPolicy::Reschedule => deadline = now,
+                OverrunPolicy::CatchUp => (),
+                OverrunPolicy::Skip => {
+                    let missed = (now - deadline).as_nanos() / period.as_nanos() + 1;
+                    let skipped = u64::try_from(missed).unwrap_or(u64::MAX);
+                    self.run_stats.skipped = self.run_stats.skipped.saturating_add(skipped);
+
+                    // Start over from now if the skipped periods cannot be added up
+                    let next = u32::try_from(missed)
+                        .ok()
+                        .and_then(|missed| period.checked_mul(missed))
+                        .and_then(|skip| deadline.checked_add(skip));
+                    match next {
+                        Some(next) => {
+                            deadline = next;
+                            clock.sleep(deadline - now);
+                        }
+                        None => {
+                            deadline = now;
+                            clock.sleep(period - elapsed.min(period));
+                        }
+                    }
+                }
+                OverrunPolicy::Fail => {
+                    self.reset();
+                    status = Status::Failed;
+                }
+            }
         }
 
-        // Figure out the time-per-cycle
-        let cycle_dur_float = freq.recip();
-        let cycle_dur = Duration::new(
-            cycle_dur_float as u64,
-            (cycle_dur_float.fract() * 1000000000.0f64) as u32,
-        );
+        status
+    }
 
-        // Now, run at the given frequency
-        let mut status = Status::Running;
+    /// Returns the statistics of the current or last call to `run` or
+    /// `run_with`.
+    pub fn run_stats(&self) -> &RunStats {
+        &self.run_stats
+    }
+
+    /// Run the behavior tree until it either succeeds or fails, without
+    /// blocking the thread.
+    ///
+    /// This behaves like `run`, except that it awaits the future returned by
+    /// `sleep` instead of sleeping the thread, so the tree can run inside an
+    /// asynchronous task. `sleep` is given the time left until the next tick
+    /// and should usually be the timer of the runtime, such as
+    /// `tokio::time::sleep`. When a tick overruns, or the frequency is
+    /// infinite, the task yields to the runtime once instead.
+    ///
+    /// While running, the tree's waker is the waker of the task, so the futures
+    /// of `AsyncAction` nodes can wake it. The run is cancelled by dropping the
+    /// returned future, which leaves the tree as it was after the last tick.
+    ///
+    /// The returned future is not `Send`, so it must run on a local executor
+    /// (e.g. `tokio::task::LocalSet`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use aspen::std_nodes::*;
+    /// # use aspen::{BehaviorTree, Status};
+    /// # use std::future::{self, Future};
+    /// # use std::pin::pin;
+    /// # use std::task::{Context, Poll, Waker};
+    /// # use std::time::Duration;
+    /// # fn block_on<F: Future>(future: F) -> F::Output {
+    /// #     let mut future = pin!(future);
+    /// #     let mut cx = Context::from_waker(Waker::noop());
+    /// #     loop {
+    /// #         if let Poll::Ready(out) = future.as_mut().poll(&mut cx) { return out; }
+    /// #     }
+    /// # }
+    /// let root = Sequence::new(vec![
+    ///     AsyncAction::new(|_: &mut ()| async { Status::Succeeded }),
+    ///     AlwaysSucceed::new(),
+    /// ]);
+    /// let mut tree = BehaviorTree::new(root);
+    ///
+    /// // A real application would use the timer of its runtime
+    /// let sleep = |_: Duration| future::ready(());
+    /// let status = block_on(tree.run_async(100.0, &mut (), sleep, None::<fn(&BehaviorTree<()>)>));
+    /// assert_eq!(status, Status::Succeeded);
+    /// ```
+    pub async fn run_async<S, T, F>(
+        &mut self,
+        freq: f64,
+        world: &mut W,
+        mut sleep: S,
+        mut hook: Option<F>,
+    ) -> Status
+    where
+        S: FnMut(Duration) -> T,
+        T: Future<Output = ()>,
+        F: FnMut(&BehaviorTree<'a, W>),
+    {
+        // Futures should wake this task while it is running the tree
+        let waker = future::poll_fn(|cx| Poll::Ready(cx.waker().clone())).await;
+        let _restore = RestoreWaker {
+            old: self.context.set_waker(Some(waker)),
+            context: self.context.clone(),
+        };
+
+        // Deal with the "special" case of a zero frequency
+        let cycle_dur = match RunConfig::new(freq).period() {
+            Some(period) => period,
+            None => {
+                debug!("Zero frequency specified, ticking once");
+                let status = self.tick(world);
+                if let Some(ref mut f) = hook {
+                    f(self);
+                }
+
+                return status;
+            }
+        };
+
+        let clock = self.context.clock();
         debug!("Ticking at {}Hz", freq);
-        while status == Status::Running {
-            let now = Instant::now();
+        loop {
+            let start = clock.now();
 
             trace!("Ticking tree");
-            status = self.tick(world);
+            let status = self.tick(world);
             if let Some(ref mut f) = hook {
                 f(self);
             }
 
-            let elapsed = now.elapsed();
+            if status.is_done() {
+                return status;
+            }
 
-            // Sleep for the remaining amount of time
-            if !status.is_done() && freq.is_finite() && elapsed < cycle_dur {
-                if elapsed < cycle_dur {
-                    // Really, the Duration would take care of the case where the
-                    // frequency is infinite. However, specifying a frequency of
-                    // infinity means running as fast a possible. In that case, I
-                    // do not want to give this thread an opportunity to sleep at
-                    // all
-                    thread::sleep(cycle_dur - elapsed);
-                } else {
+            let elapsed = clock.now() - start;
+            if elapsed < cycle_dur {
+                sleep(cycle_dur - elapsed).await;
+            } else {
+                if cycle_dur != Duration::default() {
                     warn!(
                         "Unable to tick at desired frequency: Expected {:?}, elapsed {:?}",
                         cycle_dur, elapsed
                     );
                 }
+
+                // Give the rest of the runtime a chance to make progress
+                YieldNow(false).await;
             }
         }
-
-        return status;
     }
 }
 impl<'a, W> fmt::Display for BehaviorTree<'a, W> {
+    /// Writes the tree on a single line, or as an indented tree with one node
+    /// per line when the alternate format (`{:#}`) is used.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return self.pretty().fmt(f);
+        }
+
         write!(
             f,
             "BehaviorTree:( status = {:?}, root = {} )",
@@ -121,3 +470,274 @@ impl<'a, W> fmt::Display for BehaviorTree<'a, W> {
         )
     }
 }
+
+/// Puts back the waker that a tree had before it was run asynchronously.
+struct RestoreWaker {
+    /// The context of the tree.
+    context: Rc<Context>,
+
+    /// The waker to put back.
+    old: Option<Waker>,
+}
+impl Drop for RestoreWaker {
+    fn drop(&mut self) {
+        self.context.set_waker(self.old.take());
+    }
+}
+
+/// A future that is pending the first time it is polled.
+struct YieldNow(bool);
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::context::noop_waker;
+    use crate::std_nodes::*;
+    use std::cell::Cell;
+
+    /// Polls the future until it is ready, returning the output and the
+    /// number of polls it took.
+    fn block_on<F: Future>(future: F) -> (F::Output, u32) {
+        let mut future = Box::pin(future);
+        let waker = noop_waker();
+        let mut cx = task::Context::from_waker(&waker);
+        let mut polls = 1;
+        loop {
+            if let Poll::Ready(out) = future.as_mut().poll(&mut cx) {
+                return (out, polls);
+            }
+            polls += 1;
+        }
+    }
+
+    /// Returns a node that advances the clock by the given time on every
+    /// tick and keeps running until it has been ticked `ticks` times.
+    fn slow(clock: &Arc<MockClock>, tick_time: Duration, ticks: u32) -> Node<'static, ()> {
+        let clock = clock.clone();
+        let mut count = 0;
+        InlineAction::new(move |_| {
+            clock.advance(tick_time);
+            count += 1;
+            if count < ticks {
+                Status::Running
+            } else {
+                Status::Succeeded
+            }
+        })
+    }
+
+    /// Runs a tree with the node from `slow` at 100Hz with the given policy.
+    fn run_slow(tick_time: u64, ticks: u32, policy: OverrunPolicy) -> (Status, RunStats, Duration) {
+        let clock = Arc::new(MockClock::new());
+        let mut tree = BehaviorTree::new(slow(&clock, Duration::from_millis(tick_time), ticks));
+        tree.set_clock(clock.clone());
+        let config = RunConfig::new(100.0).overrun(policy);
+        let status = tree.run_with(config, &mut (), None::<fn(&BehaviorTree<()>)>);
+
+        (status, *tree.run_stats(), clock.elapsed())
+    }
+
+    #[test]
+    fn run_on_schedule() {
+        let (status, stats, elapsed) = run_slow(4, 3, OverrunPolicy::Fail);
+
+        assert_eq!(status, Status::Succeeded);
+        assert_eq!(stats.overruns, 0);
+        assert_eq!(stats.min_period, Duration::from_millis(10));
+        assert_eq!(stats.max_jitter, Duration::default());
+        assert_eq!(elapsed, Duration::from_millis(24));
+    }
+
+    #[test]
+    fn run_invalid_freq() {
+        // Negative and NaN frequencies tick as fast as possible
+        for &freq in &[-1.0, f64::NEG_INFINITY, f64::NAN] {
+            let clock = Arc::new(MockClock::new());
+            let mut tree = BehaviorTree::new(slow(&clock, Duration::default(), 3));
+            tree.set_clock(clock.clone());
+
+            let status = tree.run(freq, &mut (), None::<fn(&BehaviorTree<()>)>);
+            assert_eq!(status, Status::Succeeded);
+            assert_eq!(tree.run_stats().ticks, 3);
+            assert_eq!(clock.elapsed(), Duration::default());
+        }
+    }
+
+    #[test]
+    fn run_overrun_reschedule() {
+        let (status, stats, elapsed) = run_slow(15, 3, OverrunPolicy::Reschedule);
+
+        // Every tick starts as soon as the one before it finished
+        assert_eq!(status, Status::Succeeded);
+        assert_eq!(stats.overruns, 2);
+        assert_eq!(stats.mean_period, Duration::from_millis(15));
+        assert_eq!(stats.max_jitter, Duration::from_millis(5));
+        assert_eq!(elapsed, Duration::from_millis(45));
+    }
+
+    #[test]
+    fn run_overrun_skip() {
+        let (status, stats, elapsed) = run_slow(15, 3, OverrunPolicy::Skip);
+
+        // Every tick misses one deadline and waits for the one after it
+        assert_eq!(status, Status::Succeeded);
+        assert_eq!(stats.ticks, 3);
+        assert_eq!(stats.overruns, 2);
+        assert_eq!(stats.skipped, 2);
+        assert_eq!(stats.min_period, Duration::from_millis(20));
+        assert_eq!(stats.max_jitter, Duration::from_millis(10));
+        assert_eq!(stats.max_tick_time, Duration::from_millis(15));
+        assert_eq!(elapsed, Duration::from_millis(55));
+    }
+
+    #[test]
+    fn run_overrun_skip_many() {
+        let clock = Arc::new(MockClock::new());
+        let mut tree = BehaviorTree::new(slow(&clock, Duration::from_secs(10), 2));
+        tree.set_clock(clock.clone());
+        let config = RunConfig::new(1e9).overrun(OverrunPolicy::Skip);
+        let status = tree.run_with(config, &mut (), None::<fn(&BehaviorTree<()>)>);
+
+        // Too many periods to skip, so the next tick starts right away
+        assert_eq!(status, Status::Succeeded);
+        assert_eq!(tree.run_stats().skipped, 10_000_000_000);
+        assert_eq!(clock.elapsed(), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn run_overrun_catch_up() {
+        let clock = Arc::new(MockClock::new());
+        let mut ticks = 0;
+        let root = InlineAction::new({
+            let clock = clock.clone();
+            move |_: &mut ()| {
+                // Only the first tick is slow
+                ticks += 1;
+                if ticks == 1 {
+                    clock.advance(Duration::from_millis(25));
+                }
+                if ticks < 5 {
+                    Status::Running
+                } else {
+                    Status::Succeeded
+                }
+            }
+        });
+        let mut tree = BehaviorTree::new(root);
+        tree.set_clock(clock.clone());
+        let config = RunConfig::new(100.0).overrun(OverrunPolicy::CatchUp);
+        tree.run_with(config, &mut (), None::<fn(&BehaviorTree<()>)>);
+
+        // The ticks for 10ms and 20ms happen right away, then the schedule
+        // continues at 30ms and 40ms
+        assert_eq!(tree.run_stats().overruns, 2);
+        assert_eq!(tree.run_stats().min_period, Duration::default());
+        assert_eq!(clock.elapsed(), Duration::from_millis(40));
+    }
+
+    #[test]
+    fn run_overrun_fail() {
+        let clock = Arc::new(MockClock::new());
+        let mut tree = BehaviorTree::new(slow(&clock, Duration::from_millis(15), 10));
+        tree.set_clock(clock.clone());
+        let config = RunConfig::new(100.0).overrun(OverrunPolicy::Fail);
+        let status = tree.run_with(config, &mut (), None::<fn(&BehaviorTree<()>)>);
+
+        assert_eq!(status, Status::Failed);
+        assert_eq!(tree.run_stats().ticks, 1);
+        assert_eq!(tree.run_stats().overruns, 1);
+        assert_eq!(tree.root().status(), None);
+    }
+
+    #[test]
+    fn run_stats_in_hook() {
+        let clock = Arc::new(MockClock::new());
+        let mut tree = BehaviorTree::new(slow(&clock, Duration::default(), 4));
+        let mut seen = Vec::new();
+        tree.run(
+            f64::INFINITY,
+            &mut (),
+            Some(|t: &BehaviorTree<()>| seen.push(t.run_stats().ticks)),
+        );
+
+        assert_eq!(seen, vec![1, 2, 3, 4]);
+        assert_eq!(tree.run_stats().overruns, 0);
+    }
+
+    #[test]
+    fn run_async_sleeps_between_ticks() {
+        let sleeps = Cell::new(0);
+        let ticks = Cell::new(0);
+        let root = InlineAction::new(|_: &mut ()| {
+            ticks.set(ticks.get() + 1);
+            if ticks.get() < 3 {
+                Status::Running
+            } else {
+                Status::Succeeded
+            }
+        });
+        let mut tree = BehaviorTree::new(root);
+        let sleep = |d: Duration| {
+            assert!(d <= Duration::from_millis(10));
+            sleeps.set(sleeps.get() + 1);
+            YieldNow(false)
+        };
+
+        let mut hooks = 0;
+        let hook = |_: &BehaviorTree<()>| hooks += 1;
+        let (status, polls) = block_on(tree.run_async(100.0, &mut (), sleep, Some(hook)));
+        assert_eq!(status, Status::Succeeded);
+        assert_eq!(hooks, 3);
+        assert_eq!(sleeps.get(), 2);
+        assert_eq!(polls, 3);
+    }
+
+    #[test]
+    fn run_async_negative_freq() {
+        let mut tree = BehaviorTree::new(Repeat::with_limit(3, AlwaysSucceed::new()));
+        let sleep = |_: Duration| -> YieldNow { panic!("Should not sleep") };
+
+        let hook = None::<fn(&BehaviorTree<()>)>;
+        let (status, _) = block_on(tree.run_async(-1.0, &mut (), sleep, hook));
+        assert_eq!(status, Status::Succeeded);
+        assert_eq!(tree.ticks(), 3);
+    }
+
+    #[test]
+    fn run_async_cancel() {
+        let mut tree = BehaviorTree::new(AlwaysRunning::new());
+        let mut world = ();
+        {
+            let run = tree.run_async(
+                f64::INFINITY,
+                &mut world,
+                |_| async {},
+                None::<fn(&BehaviorTree<()>)>,
+            );
+            let mut run = Box::pin(run);
+            let waker = noop_waker();
+            let mut cx = task::Context::from_waker(&waker);
+            for _ in 0..5 {
+                assert!(run.as_mut().poll(&mut cx).is_pending());
+            }
+        }
+
+        // Dropping the run leaves the tree running
+        assert_eq!(tree.ticks(), 5);
+        assert_eq!(tree.root().status(), Some(Status::Running));
+    }
+}
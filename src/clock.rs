@@ -0,0 +1,138 @@
+//! Sources of time for trees and the nodes in them.
+//!
+//! Every `BehaviorTree` has a `Clock`, which `run` uses to schedule ticks and
+//! which time-based nodes read through their `Context`. By default this is a
+//! `SystemClock`. Tests can give the tree a `MockClock` instead, which only
+//! moves when it is told to, so that time-based behavior can be checked
+//! without sleeping.
+//!
+//! # Examples
+//!
+//! ```
+//! # use aspen::std_nodes::*;
+//! # use aspen::clock::{Clock, MockClock};
+//! # use aspen::BehaviorTree;
+//! # use std::sync::Arc;
+//! # use std::time::Duration;
+//! let clock = Arc::new(MockClock::new());
+//! let start = clock.now();
+//! let mut tree = BehaviorTree::new(Repeat::with_limit(10, AlwaysSucceed::new()));
+//! tree.set_clock(clock.clone());
+//!
+//! // Sleeping between ticks only advances the mock clock
+//! tree.run(10.0, &mut (), None::<fn(&BehaviorTree<()>)>);
+//! assert_eq!(clock.now() - start, Duration::from_millis(900));
+//! ```
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A source of time.
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+
+    /// Blocks the current thread for the given amount of time.
+    fn sleep(&self, duration: Duration);
+}
+
+/// The clock of the operating system.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// A clock that only moves when it is advanced.
+///
+/// Sleeping on a mock clock returns immediately after advancing it by the
+/// requested time. A clock advanced past the latest time that an `Instant`
+/// can hold stays at that time.
+#[derive(Debug)]
+pub struct MockClock {
+    /// The time when the clock was created.
+    start: Instant,
+
+    /// How far the clock has been advanced since it was created.
+    elapsed: Mutex<Duration>,
+}
+impl MockClock {
+    /// Creates a clock that starts at the current time.
+    pub fn new() -> MockClock {
+        MockClock {
+            start: Instant::now(),
+            elapsed: Mutex::new(Duration::default()),
+        }
+    }
+
+    /// Moves the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        let mut elapsed = self.elapsed.lock().unwrap();
+        *elapsed = elapsed.saturating_add(duration);
+    }
+
+    /// Returns how far the clock has been advanced since it was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+impl Default for MockClock {
+    fn default() -> MockClock {
+        MockClock::new()
+    }
+}
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        let elapsed = self.elapsed();
+        self.start
+            .checked_add(elapsed)
+            .unwrap_or_else(|| latest(self.start, elapsed))
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+/// Returns the latest instant that is at most `limit` after `start`.
+fn latest(start: Instant, limit: Duration) -> Instant {
+    let from_nanos =
+        |n: u128| Duration::new((n / 1_000_000_000) as u64, (n % 1_000_000_000) as u32);
+
+    // Search for the longest duration that can still be added to the start
+    let (mut low, mut high) = (0, limit.as_nanos());
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if start.checked_add(from_nanos(mid)).is_some() {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    start + from_nanos(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clock, MockClock};
+    use std::time::Duration;
+
+    #[test]
+    fn mock_clock_saturates() {
+        let clock = MockClock::new();
+        clock.advance(Duration::MAX);
+        let end = clock.now();
+
+        clock.sleep(Duration::from_secs(1));
+        assert_eq!(clock.elapsed(), Duration::MAX);
+        assert_eq!(clock.now(), end);
+        assert!(end.checked_add(Duration::from_nanos(1)).is_none());
+    }
+}
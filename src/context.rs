@@ -0,0 +1,152 @@
+//! State that is shared by all of the nodes in a tree.
+//!
+//! Every node of a `BehaviorTree` is given a handle to the same `Context`
+//! through `Tickable::attach` when the tree is created. Nodes that need
+//! tree-wide services, such as the executor for threaded actions, keep the
+//! handle and use it while they are ticked.
+use crate::clock::{Clock, SystemClock};
+use crate::executor::{ActionExecutor, PanicPolicy, SpawnExecutor};
+use crate::observer::TreeObserver;
+use std::cell::{Cell, RefCell};
+use std::ptr;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{RawWaker, RawWakerVTable, Waker};
+use std::time::Instant;
+
+/// Tree-wide state that every node of a `BehaviorTree` holds a handle to.
+pub struct Context {
+    /// The observers that are notified of node events.
+    observers: RefCell<Vec<Box<dyn TreeObserver>>>,
+
+    /// The executor that runs the tasks of threaded actions.
+    executor: RefCell<Arc<dyn ActionExecutor>>,
+
+    /// What threaded actions do when their task panics.
+    panic_policy: Cell<PanicPolicy>,
+
+    /// Whether nodes should time their ticks.
+    profiling: Cell<bool>,
+
+    /// The source of time for the tree and its nodes.
+    clock: RefCell<Arc<dyn Clock>>,
+
+    /// The waker handed to the futures of asynchronous actions, if any.
+    waker: RefCell<Option<Waker>>,
+}
+impl Context {
+    /// Returns the executor that threaded actions should run their tasks on.
+    pub fn executor(&self) -> Arc<dyn ActionExecutor> {
+        self.executor.borrow().clone()
+    }
+
+    /// Replaces the executor for threaded actions.
+    pub(crate) fn set_executor(&self, executor: Arc<dyn ActionExecutor>) {
+        *self.executor.borrow_mut() = executor;
+    }
+
+    /// Returns what threaded actions should do when their task panics.
+    pub fn panic_policy(&self) -> PanicPolicy {
+        self.panic_policy.get()
+    }
+
+    /// Replaces the policy for panicking threaded actions.
+    pub(crate) fn set_panic_policy(&self, policy: PanicPolicy) {
+        self.panic_policy.set(policy);
+    }
+
+    /// Returns the clock of the tree.
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.borrow().clone()
+    }
+
+    /// Returns the current time according to the clock of the tree.
+    ///
+    /// Time-based nodes should use this instead of `Instant::now`, so that
+    /// they can be tested with a `MockClock`.
+    pub fn now(&self) -> Instant {
+        self.clock.borrow().now()
+    }
+
+    /// Replaces the clock of the tree.
+    pub(crate) fn set_clock(&self, clock: Arc<dyn Clock>) {
+        *self.clock.borrow_mut() = clock;
+    }
+
+    /// Returns whether nodes should keep statistics about their ticks.
+    pub fn profiling(&self) -> bool {
+        self.profiling.get()
+    }
+
+    /// Turns the statistics of nodes on or off.
+    pub(crate) fn set_profiling(&self, profiling: bool) {
+        self.profiling.set(profiling);
+    }
+
+    /// Returns the waker that asynchronous actions should poll their futures
+    /// with.
+    ///
+    /// This is the waker given to `BehaviorTree::set_waker`, or a waker that
+    /// does nothing if there is none.
+    pub fn waker(&self) -> Waker {
+        match *self.waker.borrow() {
+            Some(ref waker) => waker.clone(),
+            None => noop_waker(),
+        }
+    }
+
+    /// Replaces the waker for asynchronous actions, returning the old one.
+    pub(crate) fn set_waker(&self, waker: Option<Waker>) -> Option<Waker> {
+        self.waker.replace(waker)
+    }
+
+    /// Adds an observer to be notified of node events.
+    pub(crate) fn add_observer(&self, observer: Box<dyn TreeObserver>) {
+        self.observers.borrow_mut().push(observer);
+    }
+
+    /// Calls the given function on every observer.
+    pub(crate) fn notify<F>(&self, mut f: F)
+    where
+        F: FnMut(&mut dyn TreeObserver),
+    {
+        for observer in self.observers.borrow_mut().iter_mut() {
+            f(observer.as_mut());
+        }
+    }
+}
+impl Default for Context {
+    fn default() -> Context {
+        Context {
+            observers: RefCell::new(Vec::new()),
+            executor: RefCell::new(Arc::new(SpawnExecutor)),
+            panic_policy: Cell::new(PanicPolicy::default()),
+            profiling: Cell::new(false),
+            clock: RefCell::new(Arc::new(SystemClock)),
+            waker: RefCell::new(None),
+        }
+    }
+}
+
+/// Returns the current time according to the clock of the tree, or the
+/// system clock for nodes that are not part of a tree.
+pub(crate) fn now(context: &Option<Rc<Context>>) -> Instant {
+    match *context {
+        Some(ref context) => context.now(),
+        None => Instant::now(),
+    }
+}
+
+/// Returns a waker that does nothing when woken.
+///
+/// Futures polled with it are only polled again on the next tick.
+pub(crate) fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    // The waker holds no data, so all of its functions are trivially safe
+    unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) }
+}
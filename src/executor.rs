@@ -0,0 +1,280 @@
+//! Executors that run the tasks of threaded `Action` nodes.
+//!
+//! By default every run of an `Action` gets its own thread. A tree that runs
+//! many short actions can instead share a bounded `ThreadPool` between all of
+//! its actions with `BehaviorTree::set_executor`.
+//!
+//! # Examples
+//!
+//! ```
+//! # use aspen::std_nodes::*;
+//! # use aspen::executor::ThreadPool;
+//! # use aspen::{BehaviorTree, Status};
+//! # use std::sync::Arc;
+//! let pool = Arc::new(ThreadPool::new(2, 8));
+//! let root = Repeat::with_limit(10, Action::new(|_| Status::Succeeded));
+//! let mut tree = BehaviorTree::new(root);
+//! tree.set_executor(pool.clone());
+//!
+//! while tree.tick(&mut ()) == Status::Running {}
+//! println!("Peak queue depth: {}", pool.metrics().peak_queued);
+//! ```
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+/// What a threaded `Action` does when its task panics.
+///
+/// The panic is caught on the worker thread either way, and its message is
+/// passed to `TreeObserver::on_panic`.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum PanicPolicy {
+    /// The action fails. This is the default.
+    #[default]
+    Fail,
+
+    /// The action stays running and starts its task again on the next tick.
+    Restart,
+
+    /// The panic is resumed on the ticking thread when the action is ticked.
+    Propagate,
+}
+
+/// A task submitted to an executor.
+pub type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Runs the tasks of `Action` nodes outside of the ticking thread.
+pub trait ActionExecutor {
+    /// Starts running the job, or hands it back if the executor is too busy
+    /// to accept it.
+    ///
+    /// An action whose job is handed back stays `Running` and submits the job
+    /// again on its next tick.
+    fn try_execute(&self, job: Job) -> Result<(), Job>;
+}
+
+/// An executor that spawns a new thread for every job.
+///
+/// This is the executor used by trees that have not been given another one.
+/// It never refuses a job.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SpawnExecutor;
+impl ActionExecutor for SpawnExecutor {
+    fn try_execute(&self, job: Job) -> Result<(), Job> {
+        thread::spawn(job);
+        Ok(())
+    }
+}
+
+/// A snapshot of the state of a `ThreadPool`.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct PoolMetrics {
+    /// The number of worker threads.
+    pub workers: usize,
+
+    /// The number of workers that are currently running a job.
+    pub busy: usize,
+
+    /// The number of jobs that have not been picked up by a worker yet.
+    pub queued: usize,
+
+    /// The largest number of jobs that have been queued at once.
+    pub peak_queued: usize,
+
+    /// The number of jobs that have finished, including ones that panicked.
+    pub completed: u64,
+
+    /// The number of jobs that were refused because the queue was full.
+    pub rejected: u64,
+}
+
+/// The state shared between a pool and its workers.
+struct Shared {
+    /// The queue and the counters.
+    state: Mutex<PoolState>,
+
+    /// Signalled when a job is queued or the pool shuts down.
+    available: Condvar,
+}
+
+/// The mutable state of a pool.
+struct PoolState {
+    /// Jobs waiting for a free worker.
+    queue: VecDeque<Job>,
+
+    /// The counters reported by `ThreadPool::metrics`.
+    metrics: PoolMetrics,
+
+    /// Whether the pool is being dropped.
+    shutdown: bool,
+}
+
+/// An executor with a fixed number of worker threads and a bounded queue.
+///
+/// Jobs are refused once `capacity` of them are waiting for a worker, which
+/// keeps the actions that submitted them `Running` until there is room. A
+/// panic in a job does not take its worker down with it.
+///
+/// Dropping the pool waits for the workers to finish every job that has
+/// already been accepted.
+pub struct ThreadPool {
+    /// The state shared with the workers.
+    shared: Arc<Shared>,
+
+    /// The worker threads.
+    workers: Vec<thread::JoinHandle<()>>,
+
+    /// The largest number of jobs that may wait for a worker.
+    capacity: usize,
+}
+impl ThreadPool {
+    /// Creates a pool with the given number of workers and room for
+    /// `capacity` waiting jobs.
+    ///
+    /// # Panics
+    ///
+    /// If `workers` is zero.
+    pub fn new(workers: usize, capacity: usize) -> ThreadPool {
+        assert!(workers > 0, "A thread pool needs at least one worker");
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(PoolState {
+                queue: VecDeque::with_capacity(capacity),
+                metrics: PoolMetrics {
+                    workers,
+                    ..PoolMetrics::default()
+                },
+                shutdown: false,
+            }),
+            available: Condvar::new(),
+        });
+
+        let workers = (0..workers)
+            .map(|i| {
+                let shared = shared.clone();
+                thread::Builder::new()
+                    .name(format!("aspen-worker-{}", i))
+                    .spawn(move || work(&shared))
+                    .expect("Unable to spawn a worker thread")
+            })
+            .collect();
+
+        ThreadPool {
+            shared,
+            workers,
+            capacity,
+        }
+    }
+
+    /// Returns the current state of the pool.
+    pub fn metrics(&self) -> PoolMetrics {
+        let state = self.shared.state.lock().unwrap();
+        PoolMetrics {
+            queued: state.queue.len(),
+            ..state.metrics
+        }
+    }
+}
+impl ActionExecutor for ThreadPool {
+    fn try_execute(&self, job: Job) -> Result<(), Job> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        // A job can only skip the queue if a worker is waiting for one
+        let idle = state.metrics.workers - state.metrics.busy;
+        if state.queue.len() >= self.capacity + idle {
+            state.metrics.rejected += 1;
+            return Err(job);
+        }
+
+        state.queue.push_back(job);
+        state.metrics.peak_queued = state.metrics.peak_queued.max(state.queue.len());
+        self.shared.available.notify_one();
+        Ok(())
+    }
+}
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.available.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Runs jobs from the queue until the pool shuts down and the queue is empty.
+fn work(shared: &Shared) {
+    let mut state = shared.state.lock().unwrap();
+    loop {
+        if let Some(job) = state.queue.pop_front() {
+            state.metrics.busy += 1;
+            drop(state);
+
+            // The job reports its own failure, the worker only survives it
+            let _ = panic::catch_unwind(AssertUnwindSafe(job));
+
+            state = shared.state.lock().unwrap();
+            state.metrics.busy -= 1;
+            state.metrics.completed += 1;
+        } else if state.shutdown {
+            return;
+        } else {
+            state = shared.available.wait(state).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn backpressure() {
+        let pool = ThreadPool::new(1, 1);
+        let (tx, rx) = mpsc::channel::<()>();
+        let rx = Arc::new(Mutex::new(rx));
+        let block = || -> Job {
+            let rx = rx.clone();
+            Box::new(move || {
+                let _ = rx.lock().unwrap().recv();
+            })
+        };
+
+        // One job for the worker, one for the queue, and then the pool is full
+        assert!(pool.try_execute(block()).is_ok());
+        while pool.metrics().busy == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(pool.try_execute(block()).is_ok());
+        assert!(pool.try_execute(block()).is_err());
+
+        let metrics = pool.metrics();
+        assert_eq!(metrics.busy, 1);
+        assert_eq!(metrics.queued, 1);
+        assert_eq!(metrics.peak_queued, 1);
+        assert_eq!(metrics.rejected, 1);
+
+        tx.send(()).unwrap();
+        tx.send(()).unwrap();
+        drop(pool);
+    }
+
+    #[test]
+    fn survives_panics() {
+        let pool = ThreadPool::new(1, 4);
+        assert!(pool.try_execute(Box::new(|| panic!("Job failed"))).is_ok());
+
+        let (tx, rx) = mpsc::channel();
+        assert!(pool
+            .try_execute(Box::new(move || tx.send(1).unwrap()))
+            .is_ok());
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(1));
+
+        let metrics = pool.metrics();
+        assert_eq!(metrics.workers, 1);
+        assert!(metrics.completed >= 1);
+    }
+}
@@ -0,0 +1,82 @@
+//! Reasons for why nodes failed.
+use std::fmt;
+
+/// Why a node failed.
+///
+/// A node that fails with a reason passes it on to its ancestors, which fail
+/// with the same reason unless they give their own. The reason remembers the
+/// path of the node it came from once that node is part of a tree.
+///
+/// # Examples
+///
+/// ```
+/// # use aspen::std_nodes::*;
+/// # use aspen::{BehaviorTree, Status};
+/// let root = Sequence::new(vec![
+///     AlwaysSucceed::new(),
+///     AlwaysFail::with_reason("Door is locked").named(Some("open")),
+/// ]);
+/// let mut tree = BehaviorTree::new(root.named(Some("enter")));
+///
+/// assert_eq!(tree.tick(&mut ()), Status::Failed);
+/// let reason = tree.root().failure_reason().unwrap();
+/// assert_eq!(reason.message(), "Door is locked");
+/// assert_eq!(reason.origin(), Some("enter/open"));
+/// assert_eq!(reason.to_string(), "enter/open: Door is locked");
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FailureReason {
+    /// The description of the failure.
+    message: String,
+
+    /// The path of the node that failed, if it is known.
+    origin: Option<String>,
+}
+impl FailureReason {
+    /// Creates a reason with the given description.
+    pub fn new<S: Into<String>>(message: S) -> FailureReason {
+        FailureReason {
+            message: message.into(),
+            origin: None,
+        }
+    }
+
+    /// Returns the description of the failure.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the path of the node that the failure came from.
+    ///
+    /// This is `None` if the node was not part of a `BehaviorTree`.
+    pub fn origin(&self) -> Option<&str> {
+        self.origin.as_deref()
+    }
+
+    /// Sets the origin of the reason unless it already has one.
+    pub(crate) fn or_origin(mut self, path: Option<&str>) -> FailureReason {
+        if self.origin.is_none() {
+            self.origin = path.map(|p| p.to_owned());
+        }
+        self
+    }
+}
+impl fmt::Display for FailureReason {
+    /// Writes the origin, if known, followed by the description.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.origin {
+            Some(ref origin) => write!(f, "{}: {}", origin, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+impl<'a> From<&'a str> for FailureReason {
+    fn from(message: &'a str) -> FailureReason {
+        FailureReason::new(message)
+    }
+}
+impl From<String> for FailureReason {
+    fn from(message: String) -> FailureReason {
+        FailureReason::new(message)
+    }
+}
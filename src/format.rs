@@ -0,0 +1,921 @@
+//! Declarative descriptions of behavior trees.
+//!
+//! Trees are described in JSON. Every node is an object with a `type` that
+//! matches the `Tickable::type_name` of the node, an optional `name`, an
+//! optional `params` object and an optional `children` array:
+//!
+//! ```json
+//! {
+//!     "type": "Sequence",
+//!     "name": "patrol",
+//!     "children": [
+//!         { "type": "Repeat", "params": { "limit": 3 }, "children": [
+//!             { "type": "AlwaysSucceed" }
+//!         ] }
+//!     ]
+//! }
+//! ```
+//!
+//! The parser keeps track of where each node was found so that problems with
+//! the description can be reported with a line and column.
+//!
+//! Descriptions can also be produced from existing nodes with `Node::describe`.
+//! Their `Display` implementation writes the same format back out, with one
+//! line per leaf node so that the output diffs well.
+use std::error::Error;
+use std::fmt;
+use std::str::Chars;
+
+/// A line and column in a description, both starting at one.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Position {
+    /// The line number.
+    pub line: usize,
+
+    /// The column number, counted in characters.
+    pub column: usize,
+}
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A parameter value.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    /// The JSON `null` value.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A number. Integers are represented exactly up to 2^53.
+    Number(f64),
+    /// A string.
+    String(String),
+    /// A list of values.
+    Array(Vec<Value>),
+    /// A list of key-value pairs, in the order they were written.
+    Object(Vec<(String, Value)>),
+}
+impl Value {
+    /// Parses a JSON value.
+    pub fn parse(text: &str) -> Result<Value, ParseError> {
+        let mut parser = Parser::new(text);
+        let json = parser.value()?;
+        parser.skip_whitespace();
+        if let Some(c) = parser.peek() {
+            return Err(parser.error(format!("Unexpected '{}' after the value", c)));
+        }
+
+        Ok(json.into_value())
+    }
+
+    /// Returns the value as a boolean, if it is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a number, if it is one.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an unsigned integer, if it is one.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::Number(n) if n >= 0.0 && n.fract() == 0.0 && n < 2f64.powi(64) => Some(n as u64),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a string slice, if it is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a slice of values, if it is an array.
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match *self {
+            Value::Array(ref a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a list of key-value pairs, if it is an object.
+    pub fn as_object(&self) -> Option<&[(String, Value)]> {
+        match *self {
+            Value::Object(ref o) => Some(o),
+            _ => None,
+        }
+    }
+
+    /// Returns a short description of the kind of value.
+    fn kind(&self) -> &'static str {
+        match *self {
+            Value::Null => "null",
+            Value::Bool(_) => "a boolean",
+            Value::Number(_) => "a number",
+            Value::String(_) => "a string",
+            Value::Array(_) => "an array",
+            Value::Object(_) => "an object",
+        }
+    }
+}
+impl fmt::Display for Value {
+    /// Writes the value as compact JSON.
+    ///
+    /// JSON has no way to write an infinite or NaN number, so such a number
+    /// fails with `fmt::Error`. Since `to_string` panics on that error, write
+    /// values that may not be finite with `write!` instead.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Value::Number(_) => Err(fmt::Error),
+            Value::String(ref s) => write_string(f, s),
+            Value::Array(ref a) => {
+                write!(f, "[")?;
+                for (i, v) in a.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { ", " };
+                    write!(f, "{}{}", sep, v)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(ref o) => write_object(f, o),
+        }
+    }
+}
+
+/// Writes a string as a quoted and escaped JSON string.
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Writes a list of key-value pairs as a compact JSON object.
+fn write_object(f: &mut fmt::Formatter, fields: &[(String, Value)]) -> fmt::Result {
+    if fields.is_empty() {
+        return write!(f, "{{}}");
+    }
+
+    write!(f, "{{ ")?;
+    for (i, (k, v)) in fields.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write_string(f, k)?;
+        write!(f, ": {}", v)?;
+    }
+    write!(f, " }}")
+}
+
+/// The description of a single node and, recursively, its children.
+#[derive(Clone, PartialEq, Debug)]
+pub struct NodeDesc {
+    /// The type of the node, as returned by `Tickable::type_name`.
+    pub type_name: String,
+
+    /// The name of the node, if it differs from the type name.
+    pub name: Option<String>,
+
+    /// The parameters of the node, in order.
+    pub params: Vec<(String, Value)>,
+
+    /// The descriptions of the children of this node.
+    pub children: Vec<NodeDesc>,
+
+    /// Where the node was found, if it was parsed from text.
+    pub position: Option<Position>,
+}
+impl NodeDesc {
+    /// Creates a description of a node without a name, parameters or children.
+    pub fn new<S: Into<String>>(type_name: S) -> NodeDesc {
+        NodeDesc {
+            type_name: type_name.into(),
+            name: None,
+            params: Vec::new(),
+            children: Vec::new(),
+            position: None,
+        }
+    }
+
+    /// Returns the parameter with the given key, if present.
+    pub fn param(&self, key: &str) -> Option<&Value> {
+        self.params.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Parses the JSON description of a node.
+    pub fn parse(text: &str) -> Result<NodeDesc, ParseError> {
+        let mut parser = Parser::new(text);
+        let json = parser.value()?;
+        parser.skip_whitespace();
+        if let Some(c) = parser.peek() {
+            return Err(parser.error(format!("Unexpected '{}' after the root node", c)));
+        }
+
+        NodeDesc::from_json(json)
+    }
+
+    /// Converts the description into a JSON object.
+    ///
+    /// Unlike the `Display` implementation, the value is written on a single
+    /// line, but it is still in the format that `NodeDesc::parse` reads.
+    pub fn to_value(&self) -> Value {
+        let mut fields = vec![("type".to_owned(), Value::String(self.type_name.clone()))];
+        if let Some(ref name) = self.name {
+            fields.push(("name".to_owned(), Value::String(name.clone())));
+        }
+        if !self.params.is_empty() {
+            fields.push(("params".to_owned(), Value::Object(self.params.clone())));
+        }
+        if !self.children.is_empty() {
+            let children = self.children.iter().map(NodeDesc::to_value).collect();
+            fields.push(("children".to_owned(), Value::Array(children)));
+        }
+
+        Value::Object(fields)
+    }
+
+    /// Converts a parsed JSON value into a node description.
+    fn from_json(json: Json) -> Result<NodeDesc, ParseError> {
+        let position = json.position;
+        let fields = match json.kind {
+            JsonKind::Object(fields) => fields,
+            other => {
+                return Err(ParseError::new(
+                    position,
+                    format!(
+                        "Expected a node object, found {}",
+                        other.into_value().kind()
+                    ),
+                ))
+            }
+        };
+
+        let mut type_name = None;
+        let mut desc = NodeDesc::new("");
+        desc.position = Some(position);
+        for (key, value) in fields {
+            let field_pos = value.position;
+            match key.as_str() {
+                "type" => type_name = Some(value.into_string("type")?),
+                "name" => desc.name = Some(value.into_string("name")?),
+                "params" => match value.into_value() {
+                    Value::Object(params) => desc.params = params,
+                    other => {
+                        return Err(ParseError::new(
+                            field_pos,
+                            format!(
+                                "Expected \"params\" to be an object, found {}",
+                                other.kind()
+                            ),
+                        ))
+                    }
+                },
+                "children" => match value.kind {
+                    JsonKind::Array(children) => {
+                        for child in children {
+                            desc.children.push(NodeDesc::from_json(child)?);
+                        }
+                    }
+                    other => {
+                        return Err(ParseError::new(
+                            field_pos,
+                            format!(
+                                "Expected \"children\" to be an array, found {}",
+                                other.into_value().kind()
+                            ),
+                        ))
+                    }
+                },
+                _ => {
+                    return Err(ParseError::new(
+                        field_pos,
+                        format!("Unknown node field \"{}\"", key),
+                    ))
+                }
+            }
+        }
+
+        match type_name {
+            Some(t) => {
+                desc.type_name = t;
+                Ok(desc)
+            }
+            None => Err(ParseError::new(position, "Node is missing its \"type\"")),
+        }
+    }
+
+    /// Writes this node at the given indentation level.
+    fn write(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        let indent = "    ".repeat(depth);
+        write!(f, "{}{{ \"type\": ", indent)?;
+        write_string(f, &self.type_name)?;
+        if let Some(ref name) = self.name {
+            write!(f, ", \"name\": ")?;
+            write_string(f, name)?;
+        }
+        if !self.params.is_empty() {
+            write!(f, ", \"params\": ")?;
+            write_object(f, &self.params)?;
+        }
+
+        if self.children.is_empty() {
+            return write!(f, " }}");
+        }
+
+        writeln!(f, ", \"children\": [")?;
+        for (i, child) in self.children.iter().enumerate() {
+            child.write(f, depth + 1)?;
+            let sep = if i + 1 < self.children.len() { "," } else { "" };
+            writeln!(f, "{}", sep)?;
+        }
+        write!(f, "{}] }}", indent)
+    }
+}
+impl fmt::Display for NodeDesc {
+    /// Writes the description in the format that `NodeDesc::parse` reads.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+/// An error in the syntax or structure of a description.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    /// Where the error was found.
+    position: Position,
+
+    /// What went wrong.
+    message: String,
+}
+impl ParseError {
+    /// Creates a new error at the given position.
+    fn new<S: Into<String>>(position: Position, message: S) -> ParseError {
+        ParseError {
+            position,
+            message: message.into(),
+        }
+    }
+
+    /// Returns where the error was found.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Returns a description of the error without the position.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.position, self.message)
+    }
+}
+impl Error for ParseError {}
+
+/// A JSON value annotated with where it started.
+struct Json {
+    /// The position of the first character of the value.
+    position: Position,
+
+    /// The value itself.
+    kind: JsonKind,
+}
+impl Json {
+    /// Converts the value into a string, failing if it is not one.
+    fn into_string(self, field: &str) -> Result<String, ParseError> {
+        match self.kind {
+            JsonKind::String(s) => Ok(s),
+            other => Err(ParseError::new(
+                self.position,
+                format!(
+                    "Expected \"{}\" to be a string, found {}",
+                    field,
+                    other.into_value().kind()
+                ),
+            )),
+        }
+    }
+
+    /// Strips the positions from the value.
+    fn into_value(self) -> Value {
+        self.kind.into_value()
+    }
+}
+
+/// The contents of a `Json` value.
+enum JsonKind {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+impl JsonKind {
+    /// Strips the positions from the value.
+    fn into_value(self) -> Value {
+        match self {
+            JsonKind::Null => Value::Null,
+            JsonKind::Bool(b) => Value::Bool(b),
+            JsonKind::Number(n) => Value::Number(n),
+            JsonKind::String(s) => Value::String(s),
+            JsonKind::Array(a) => Value::Array(a.into_iter().map(Json::into_value).collect()),
+            JsonKind::Object(o) => {
+                Value::Object(o.into_iter().map(|(k, v)| (k, v.into_value())).collect())
+            }
+        }
+    }
+}
+
+/// How deeply arrays and objects may be nested before parsing fails, so that
+/// hostile input cannot overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// A small recursive descent JSON parser that tracks positions.
+struct Parser<'t> {
+    /// The remaining input.
+    chars: std::iter::Peekable<Chars<'t>>,
+
+    /// The position of the next character.
+    position: Position,
+
+    /// How many arrays and objects the next value is nested in.
+    depth: usize,
+}
+impl<'t> Parser<'t> {
+    /// Creates a parser at the start of the text.
+    fn new(text: &'t str) -> Parser<'t> {
+        Parser {
+            chars: text.chars().peekable(),
+            position: Position { line: 1, column: 1 },
+            depth: 0,
+        }
+    }
+
+    /// Creates an error at the current position.
+    fn error<S: Into<String>>(&self, message: S) -> ParseError {
+        ParseError::new(self.position, message)
+    }
+
+    /// Returns the next character without consuming it.
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().cloned()
+    }
+
+    /// Consumes the next character.
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+
+        Some(c)
+    }
+
+    /// Consumes the given character or fails.
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.bump();
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("Expected '{}', found '{}'", expected, c))),
+            None => Err(self.error(format!("Expected '{}', found the end of input", expected))),
+        }
+    }
+
+    /// Skips over any whitespace.
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Parses any value.
+    fn value(&mut self) -> Result<Json, ParseError> {
+        self.skip_whitespace();
+        let position = self.position;
+        let kind = match self.peek() {
+            Some('{') | Some('[') => self.nested()?,
+            Some('"') => JsonKind::String(self.string()?),
+            Some(c) if c == '-' || c.is_ascii_digit() => JsonKind::Number(self.number()?),
+            Some(c) if c.is_alphabetic() => match self.word().as_str() {
+                "null" => JsonKind::Null,
+                "true" => JsonKind::Bool(true),
+                "false" => JsonKind::Bool(false),
+                w => {
+                    return Err(ParseError::new(
+                        position,
+                        format!("Unexpected word \"{}\"", w),
+                    ))
+                }
+            },
+            Some(c) => return Err(self.error(format!("Unexpected '{}'", c))),
+            None => return Err(self.error("Unexpected end of input")),
+        };
+
+        Ok(Json { position, kind })
+    }
+
+    /// Parses an object or an array, unless it is nested too deeply.
+    fn nested(&mut self) -> Result<JsonKind, ParseError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error(format!("Nested more than {} levels deep", MAX_DEPTH)));
+        }
+
+        self.depth += 1;
+        let kind = if self.peek() == Some('{') {
+            self.object()
+        } else {
+            self.array()
+        };
+        self.depth -= 1;
+        kind
+    }
+
+    /// Parses an object.
+    fn object(&mut self) -> Result<JsonKind, ParseError> {
+        self.expect('{')?;
+        let mut fields: Vec<(String, Json)> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.bump();
+            return Ok(JsonKind::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key_pos = self.position;
+            if self.peek() != Some('"') {
+                return Err(self.error("Expected a string key"));
+            }
+            let key = self.string()?;
+            if fields.iter().any(|(k, _)| *k == key) {
+                return Err(ParseError::new(
+                    key_pos,
+                    format!("Duplicate key \"{}\"", key),
+                ));
+            }
+
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.value()?;
+            fields.push((key, value));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some('}') => {
+                    self.bump();
+                    return Ok(JsonKind::Object(fields));
+                }
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+    }
+
+    /// Parses an array.
+    fn array(&mut self) -> Result<JsonKind, ParseError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.bump();
+            return Ok(JsonKind::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some(']') => {
+                    self.bump();
+                    return Ok(JsonKind::Array(items));
+                }
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+
+    /// Parses a string, including the quotes.
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            let escape_pos = self.position;
+            match self.bump() {
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    let c = match self.bump() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape(escape_pos)?,
+                        _ => return Err(ParseError::new(escape_pos, "Invalid escape sequence")),
+                    };
+                    s.push(c);
+                }
+                Some(c) if c < '\u{20}' => {
+                    return Err(ParseError::new(escape_pos, "Control character in string"))
+                }
+                Some(c) => s.push(c),
+                None => return Err(self.error("Unterminated string")),
+            }
+        }
+    }
+
+    /// Parses the digits of a `\u` escape, including surrogate pairs.
+    fn unicode_escape(&mut self, start: Position) -> Result<char, ParseError> {
+        let high = self.hex4(start)?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.bump() != Some('\\') || self.bump() != Some('u') {
+                return Err(ParseError::new(start, "Unpaired surrogate in escape"));
+            }
+            let low = self.hex4(start)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(ParseError::new(start, "Unpaired surrogate in escape"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        std::char::from_u32(code).ok_or_else(|| ParseError::new(start, "Invalid unicode escape"))
+    }
+
+    /// Parses four hexadecimal digits.
+    fn hex4(&mut self, start: Position) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .bump()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| ParseError::new(start, "Invalid unicode escape"))?;
+            code = code * 16 + digit;
+        }
+
+        Ok(code)
+    }
+
+    /// Parses a number.
+    fn number(&mut self) -> Result<f64, ParseError> {
+        let start = self.position;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() || "+-.eE".contains(c) {
+                text.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+
+        let invalid = || ParseError::new(start, format!("Invalid number \"{}\"", text));
+        let digits = text.strip_prefix('-').unwrap_or(&text);
+        if digits.starts_with('0') && digits[1..].starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        match text.parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(n),
+            Ok(_) => Err(ParseError::new(
+                start,
+                format!("Number \"{}\" is out of range", text),
+            )),
+            Err(_) => Err(invalid()),
+        }
+    }
+
+    /// Parses a run of alphabetic characters.
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() {
+                word.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+
+        word
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tree() {
+        let desc = NodeDesc::parse(
+            r#"{
+                "type": "Sequence",
+                "name": "root",
+                "children": [
+                    { "type": "Repeat", "params": { "limit": 3, "tag": "a\nbé" } },
+                    { "type": "AlwaysFail" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(desc.type_name, "Sequence");
+        assert_eq!(desc.name.as_deref(), Some("root"));
+        assert_eq!(desc.position, Some(Position { line: 1, column: 1 }));
+        assert_eq!(desc.children.len(), 2);
+
+        let repeat = &desc.children[0];
+        assert_eq!(repeat.param("limit").and_then(Value::as_u64), Some(3));
+        assert_eq!(
+            repeat.param("tag").and_then(Value::as_str),
+            Some("a\nb\u{e9}")
+        );
+        assert_eq!(
+            repeat.position,
+            Some(Position {
+                line: 5,
+                column: 21
+            })
+        );
+        assert_eq!(desc.children[1].type_name, "AlwaysFail");
+    }
+
+    #[test]
+    fn write_and_parse() {
+        let mut leaf = NodeDesc::new("Condition");
+        leaf.name = Some("say \"hi\"\n".to_owned());
+        let mut desc = NodeDesc::new("Parallel");
+        desc.params.push(("required_successes".to_owned(), Value::Number(1.0)));
+        desc.params.push((
+            "extra".to_owned(),
+            Value::Array(vec![Value::Null, Value::Bool(true), Value::Number(0.5)]),
+        ));
+        desc.children.push(leaf);
+        desc.children.push(NodeDesc::new("AlwaysRunning"));
+
+        let text = desc.to_string();
+        assert_eq!(
+            text,
+            concat!(
+                "{ \"type\": \"Parallel\", ",
+                "\"params\": { \"required_successes\": 1, \"extra\": [null, true, 0.5] }, ",
+                "\"children\": [\n",
+                "    { \"type\": \"Condition\", \"name\": \"say \\\"hi\\\"\\n\" },\n",
+                "    { \"type\": \"AlwaysRunning\" }\n",
+                "] }"
+            )
+        );
+
+        let mut parsed = NodeDesc::parse(&text).unwrap();
+        parsed.position = None;
+        for child in parsed.children.iter_mut() {
+            child.position = None;
+        }
+        assert_eq!(parsed, desc);
+
+        let line = desc.to_value().to_string();
+        assert!(!line.contains('\n'));
+        let mut parsed = NodeDesc::parse(&line).unwrap();
+        parsed.position = None;
+        for child in parsed.children.iter_mut() {
+            child.position = None;
+        }
+        assert_eq!(parsed, desc);
+        assert_eq!(Value::parse(&line).unwrap(), desc.to_value());
+    }
+
+    #[test]
+    fn syntax_errors_have_positions() {
+        let err =
+            NodeDesc::parse("{\n  \"type\": \"Invert\",\n  \"children\": [ nope ]\n}").unwrap_err();
+        assert_eq!(
+            err.position(),
+            Position {
+                line: 3,
+                column: 17
+            }
+        );
+
+        let err = NodeDesc::parse("{ \"type\": \"Invert\" } x").unwrap_err();
+        assert_eq!(
+            err.position(),
+            Position {
+                line: 1,
+                column: 22
+            }
+        );
+
+        let err = NodeDesc::parse("{ \"type\": \"Invert\"").unwrap_err();
+        assert_eq!(
+            err.position(),
+            Position {
+                line: 1,
+                column: 19
+            }
+        );
+    }
+
+    #[test]
+    fn structure_errors() {
+        let err = NodeDesc::parse("{ \"name\": \"x\" }").unwrap_err();
+        assert_eq!(err.message(), "Node is missing its \"type\"");
+
+        let err = NodeDesc::parse("{ \"type\": 1 }").unwrap_err();
+        assert_eq!(
+            err.position(),
+            Position {
+                line: 1,
+                column: 11
+            }
+        );
+
+        let err = NodeDesc::parse("{ \"type\": \"A\", \"kids\": [] }").unwrap_err();
+        assert_eq!(err.message(), "Unknown node field \"kids\"");
+    }
+
+    #[test]
+    fn strict_values() {
+        assert_eq!(Value::parse("-0.5e1").unwrap(), Value::Number(-5.0));
+        assert_eq!(Value::parse("0").unwrap(), Value::Number(0.0));
+        assert_eq!(
+            Value::parse("007").unwrap_err().message(),
+            "Invalid number \"007\""
+        );
+        assert!(Value::parse("-01").is_err());
+        assert_eq!(
+            Value::parse("1e400").unwrap_err().message(),
+            "Number \"1e400\" is out of range"
+        );
+
+        // Only the characters below U+0020 must be escaped
+        assert_eq!(
+            Value::parse("\"a\u{7f}\u{85}\"").unwrap(),
+            Value::String("a\u{7f}\u{85}".to_owned())
+        );
+        assert!(Value::parse("\"a\u{1f}\"").is_err());
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(Value::parse(&nested(MAX_DEPTH)).is_ok());
+
+        let err = Value::parse(&nested(100_000)).unwrap_err();
+        assert_eq!(err.message(), "Nested more than 128 levels deep");
+        assert_eq!(err.position().column, MAX_DEPTH + 1);
+    }
+
+    #[test]
+    fn non_finite_numbers_fail_to_write() {
+        use std::fmt::Write;
+
+        let value = Value::Array(vec![Value::Number(f64::INFINITY)]);
+        assert!(write!(String::new(), "{}", value).is_err());
+
+        let mut desc = NodeDesc::new("Wait");
+        desc.params.push(("duration".to_owned(), Value::Number(f64::NAN)));
+        assert!(write!(String::new(), "{}", desc).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! Exporters that render the structure and state of a tree as a graph.
+//!
+//! Both Graphviz DOT and Mermaid flowcharts are supported. Nodes are colored
+//! by their current status, so rendering the tree from the hook passed to
+//! `BehaviorTree::run` produces a snapshot after every tick.
+//!
+//! # Examples
+//!
+//! ```
+//! # use aspen::std_nodes::*;
+//! # use aspen::node::Tickable;
+//! # use aspen::graph;
+//! let mut root = Sequence::new(vec![AlwaysSucceed::new(), AlwaysRunning::new()]);
+//! root.tick(&mut ());
+//!
+//! let dot = graph::to_dot(&root);
+//! assert!(dot.contains("n0 -> n1;"));
+//!
+//! let mermaid = graph::to_mermaid(&root);
+//! assert!(mermaid.contains("n2[\"AlwaysRunning\"]:::running"));
+//! ```
+use crate::node::{Node, Tickable};
+use crate::status::Status;
+use std::fmt::Write;
+
+/// Returns the fill color used for a node with the given status.
+fn color(status: Option<Status>) -> &'static str {
+    match status {
+        None => "#e0e0e0",
+        Some(Status::Running) => "#f0c674",
+        Some(Status::Succeeded) => "#b5bd68",
+        Some(Status::Failed) => "#cc6666",
+    }
+}
+
+/// Returns the Mermaid class used for a node with the given status.
+fn class(status: Option<Status>) -> &'static str {
+    match status {
+        None => "idle",
+        Some(Status::Running) => "running",
+        Some(Status::Succeeded) => "succeeded",
+        Some(Status::Failed) => "failed",
+    }
+}
+
+/// Returns the label of a node, which includes the type if it was renamed.
+fn label<W>(node: &Node<W>) -> String {
+    if node.name() == node.type_name() {
+        node.name().to_owned()
+    } else {
+        format!("{}\n({})", node.name(), node.type_name())
+    }
+}
+
+/// Visits the node and its descendants in depth-first order.
+///
+/// The visitor is given the index of the node, the index of its parent and
+/// the node itself. Indices are assigned in the order nodes are visited.
+fn walk<'n, W, F>(node: &'n Node<W>, parent: Option<usize>, next: &mut usize, f: &mut F)
+where
+    F: FnMut(usize, Option<usize>, &'n Node<W>),
+{
+    let index = *next;
+    *next += 1;
+    f(index, parent, node);
+    for child in node.children() {
+        walk(child, Some(index), next, f);
+    }
+}
+
+/// Renders the node and its descendants as a Graphviz DOT digraph.
+pub fn to_dot<W>(root: &Node<W>) -> String {
+    let mut nodes = String::new();
+    let mut edges = String::new();
+    walk(root, None, &mut 0, &mut |index, parent, node| {
+        let label = label(node)
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        writeln!(
+            nodes,
+            "    n{} [label=\"{}\", fillcolor=\"{}\"];",
+            index,
+            label,
+            color(node.status())
+        )
+        .unwrap();
+
+        if let Some(parent) = parent {
+            writeln!(edges, "    n{} -> n{};", parent, index).unwrap();
+        }
+    });
+
+    format!(
+        "digraph BehaviorTree {{\n    node [shape=box, style=\"rounded,filled\"];\n{}{}}}\n",
+        nodes, edges
+    )
+}
+
+/// Renders the node and its descendants as a Mermaid flowchart.
+pub fn to_mermaid<W>(root: &Node<W>) -> String {
+    let mut out = String::from("graph TD\n");
+    for status in [
+        None,
+        Some(Status::Running),
+        Some(Status::Succeeded),
+        Some(Status::Failed),
+    ] {
+        writeln!(
+            out,
+            "    classDef {} fill:{};",
+            class(status),
+            color(status)
+        )
+        .unwrap();
+    }
+
+    walk(root, None, &mut 0, &mut |index, parent, node| {
+        let label = label(node).replace('"', "#quot;").replace('\n', "<br/>");
+        writeln!(
+            out,
+            "    n{}[\"{}\"]:::{}",
+            index,
+            label,
+            class(node.status())
+        )
+        .unwrap();
+
+        if let Some(parent) = parent {
+            writeln!(out, "    n{} --> n{}", parent, index).unwrap();
+        }
+    });
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std_nodes::*;
+
+    #[test]
+    fn dot() {
+        let mut root = Selector::new(vec![
+            AlwaysFail::new().named(Some("say \"no\"")),
+            AlwaysSucceed::new(),
+        ]);
+        root.tick(&mut ());
+
+        assert_eq!(
+            to_dot(&root),
+            concat!(
+                "digraph BehaviorTree {\n",
+                "    node [shape=box, style=\"rounded,filled\"];\n",
+                "    n0 [label=\"Selector\", fillcolor=\"#b5bd68\"];\n",
+                "    n1 [label=\"say \\\"no\\\"\\n(AlwaysFail)\", fillcolor=\"#cc6666\"];\n",
+                "    n2 [label=\"AlwaysSucceed\", fillcolor=\"#b5bd68\"];\n",
+                "    n0 -> n1;\n",
+                "    n0 -> n2;\n",
+                "}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn mermaid() {
+        let root: Node<()> = Invert::new(AlwaysRunning::new().named(Some("wait")));
+        let out = to_mermaid(&root);
+
+        assert!(out.starts_with("graph TD\n"));
+        assert!(out.contains("    n0[\"Invert\"]:::idle\n"));
+        assert!(out.contains("    n1[\"wait<br/>(AlwaysRunning)\"]:::idle\n"));
+        assert!(out.ends_with("    n0 --> n1\n"));
+    }
+}
@@ -14,12 +14,41 @@
 #[macro_use]
 extern crate log;
 
+pub mod blackboard;
+
 mod bt;
 pub use crate::bt::BehaviorTree;
 
+pub mod clock;
+
+pub mod context;
+
+pub mod executor;
+
+mod failure;
+pub use crate::failure::FailureReason;
+
+pub mod format;
+
+pub mod graph;
+
+pub mod loader;
+
 pub mod node;
 
+pub mod observer;
+
+pub mod pretty;
+
+pub mod profile;
+
+pub mod progress;
+
+pub mod runner;
+
 mod status;
-pub use crate::status::Status;
+pub use crate::status::{ParseStatusError, Status};
 
 pub mod std_nodes;
+
+pub mod trace;
@@ -0,0 +1,768 @@
+//! Building behavior trees from declarative descriptions.
+//!
+//! A `Registry` maps node type names to factories that construct the node
+//! from its parameters and already constructed children. The registry
+//! returned by `Registry::with_std_nodes` knows about all of the standard
+//! nodes that can be described without closures. Nodes such as `Condition`
+//! or `Action` need to be registered by the application, usually under a
+//! type name of its own choosing.
+//!
+//! See the `format` module for the layout of a description.
+//!
+//! # Examples
+//!
+//! ```
+//! # use aspen::loader::{Arity, Registry};
+//! # use aspen::std_nodes::*;
+//! # use aspen::Status;
+//! let mut registry = Registry::with_std_nodes();
+//! registry.register("IsEven", Arity::Exactly(0), |_, _| {
+//!     Ok(Condition::new(|x: &u32| x % 2 == 0))
+//! });
+//!
+//! let mut tree = registry.load(r#"{
+//!     "type": "Invert",
+//!     "children": [ { "type": "IsEven" } ]
+//! }"#).unwrap();
+//!
+//! assert_eq!(tree.tick(&mut 3), Status::Succeeded);
+//! ```
+use crate::blackboard::HasBlackboard;
+use crate::bt::BehaviorTree;
+use crate::failure::FailureReason;
+use crate::format::{NodeDesc, ParseError, Position, Value};
+use crate::node::{Node, Tickable};
+use crate::status::Status;
+use crate::std_nodes::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// The number of children a node type accepts.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Arity {
+    /// Exactly the given number of children.
+    Exactly(usize),
+    /// Up to and including the given number of children.
+    AtMost(usize),
+    /// Any number of children.
+    Any,
+}
+impl Arity {
+    /// Returns `true` if the given number of children is acceptable.
+    pub fn accepts(self, count: usize) -> bool {
+        match self {
+            Arity::Exactly(n) => count == n,
+            Arity::AtMost(n) => count <= n,
+            Arity::Any => true,
+        }
+    }
+}
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let plural = |n| if n == 1 { "child" } else { "children" };
+        match *self {
+            Arity::Exactly(n) => write!(f, "exactly {} {}", n, plural(n)),
+            Arity::AtMost(n) => write!(f, "at most {} {}", n, plural(n)),
+            Arity::Any => write!(f, "any number of children"),
+        }
+    }
+}
+
+/// The kinds of errors that can occur while loading a tree.
+#[derive(Debug)]
+pub enum LoadErrorKind {
+    /// The description could not be read.
+    Io(io::Error),
+    /// The description was not valid.
+    Parse(ParseError),
+    /// There is no factory registered for the node type.
+    UnknownType(String),
+    /// The node has the wrong number of children for its type.
+    ChildCount {
+        /// The type of the node.
+        type_name: String,
+        /// The number of children the type accepts.
+        expected: Arity,
+        /// The number of children in the description.
+        found: usize,
+    },
+    /// A required parameter is missing.
+    MissingParameter {
+        /// The type of the node.
+        type_name: String,
+        /// The name of the parameter.
+        param: String,
+    },
+    /// A parameter is not used by the node type.
+    UnknownParameter {
+        /// The type of the node.
+        type_name: String,
+        /// The name of the parameter.
+        param: String,
+    },
+    /// A parameter has a value that the node cannot use.
+    InvalidParameter {
+        /// The type of the node.
+        type_name: String,
+        /// The name of the parameter.
+        param: String,
+        /// A description of what was expected.
+        expected: String,
+    },
+    /// A factory specific error.
+    Custom(String),
+}
+
+/// An error that occurred while loading a tree.
+#[derive(Debug)]
+pub struct LoadError {
+    /// Where in the description the error occurred, if known.
+    position: Option<Position>,
+
+    /// What went wrong.
+    kind: LoadErrorKind,
+}
+impl LoadError {
+    /// Creates a new error at the given position.
+    pub fn new(position: Option<Position>, kind: LoadErrorKind) -> LoadError {
+        LoadError { position, kind }
+    }
+
+    /// Returns where in the description the error occurred, if known.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// Returns the kind of error.
+    pub fn kind(&self) -> &LoadErrorKind {
+        &self.kind
+    }
+}
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(pos) = self.position {
+            write!(f, "{}: ", pos)?;
+        }
+
+        match self.kind {
+            LoadErrorKind::Io(ref e) => write!(f, "Unable to read tree: {}", e),
+            LoadErrorKind::Parse(ref e) => write!(f, "{}", e.message()),
+            LoadErrorKind::UnknownType(ref t) => write!(f, "Unknown node type \"{}\"", t),
+            LoadErrorKind::ChildCount {
+                ref type_name,
+                expected,
+                found,
+            } => write!(f, "\"{}\" expects {}, found {}", type_name, expected, found),
+            LoadErrorKind::MissingParameter {
+                ref type_name,
+                ref param,
+            } => write!(f, "\"{}\" is missing parameter \"{}\"", type_name, param),
+            LoadErrorKind::UnknownParameter {
+                ref type_name,
+                ref param,
+            } => write!(f, "\"{}\" has no parameter \"{}\"", type_name, param),
+            LoadErrorKind::InvalidParameter {
+                ref type_name,
+                ref param,
+                ref expected,
+            } => write!(
+                f,
+                "Parameter \"{}\" of \"{}\" must be {}",
+                param, type_name, expected
+            ),
+            LoadErrorKind::Custom(ref msg) => write!(f, "{}", msg),
+        }
+    }
+}
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.kind {
+            LoadErrorKind::Io(ref e) => Some(e),
+            LoadErrorKind::Parse(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<ParseError> for LoadError {
+    fn from(e: ParseError) -> LoadError {
+        LoadError::new(Some(e.position()), LoadErrorKind::Parse(e))
+    }
+}
+
+/// The parameters of a node that is being loaded.
+///
+/// The accessors report errors at the position of the node. Parameters
+/// that are never looked up are reported as unknown once the factory returns.
+pub struct Params<'d> {
+    /// The description of the node.
+    desc: &'d NodeDesc,
+
+    /// The keys that have been looked up.
+    used: RefCell<Vec<String>>,
+}
+impl<'d> Params<'d> {
+    /// Wraps the parameters of the given description.
+    fn new(desc: &'d NodeDesc) -> Params<'d> {
+        Params {
+            desc,
+            used: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Returns the type name of the node.
+    pub fn type_name(&self) -> &str {
+        &self.desc.type_name
+    }
+
+    /// Returns the name of the node, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.desc.name.as_deref()
+    }
+
+    /// Returns where the node is in the description, if known.
+    pub fn position(&self) -> Option<Position> {
+        self.desc.position
+    }
+
+    /// Returns the raw value of a parameter, if present.
+    pub fn get(&self, key: &str) -> Option<&'d Value> {
+        self.used.borrow_mut().push(key.to_owned());
+        self.desc.param(key)
+    }
+
+    /// Returns an error for the first parameter that has not been looked up,
+    /// if any.
+    fn check_unused(&self) -> Result<(), LoadError> {
+        let used = self.used.borrow();
+        match self.desc.params.iter().find(|(k, _)| !used.contains(k)) {
+            Some((key, _)) => Err(self.error(LoadErrorKind::UnknownParameter {
+                type_name: self.desc.type_name.clone(),
+                param: key.clone(),
+            })),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns an error for a missing parameter.
+    pub fn missing(&self, key: &str) -> LoadError {
+        self.error(LoadErrorKind::MissingParameter {
+            type_name: self.desc.type_name.clone(),
+            param: key.to_owned(),
+        })
+    }
+
+    /// Returns an error for a parameter that has an unusable value.
+    pub fn invalid<S: Into<String>>(&self, key: &str, expected: S) -> LoadError {
+        self.error(LoadErrorKind::InvalidParameter {
+            type_name: self.desc.type_name.clone(),
+            param: key.to_owned(),
+            expected: expected.into(),
+        })
+    }
+
+    /// Returns an error of the given kind at the position of the node.
+    pub fn error(&self, kind: LoadErrorKind) -> LoadError {
+        LoadError::new(self.desc.position, kind)
+    }
+
+    /// Gets an optional parameter, converting it with the given function.
+    pub fn optional<T, F>(
+        &self,
+        key: &str,
+        expected: &str,
+        convert: F,
+    ) -> Result<Option<T>, LoadError>
+    where
+        F: FnOnce(&'d Value) -> Option<T>,
+    {
+        match self.get(key) {
+            Some(value) => convert(value)
+                .map(Some)
+                .ok_or_else(|| self.invalid(key, expected)),
+            None => Ok(None),
+        }
+    }
+
+    /// Gets a required parameter, converting it with the given function.
+    pub fn require<T, F>(&self, key: &str, expected: &str, convert: F) -> Result<T, LoadError>
+    where
+        F: FnOnce(&'d Value) -> Option<T>,
+    {
+        self.optional(key, expected, convert)?
+            .ok_or_else(|| self.missing(key))
+    }
+
+    /// Gets an optional unsigned integer parameter.
+    pub fn optional_u32(&self, key: &str) -> Result<Option<u32>, LoadError> {
+        self.optional(key, "an unsigned 32-bit integer", |v| {
+            v.as_u64().and_then(|n| u32::try_from(n).ok())
+        })
+    }
+
+    /// Gets a required unsigned integer parameter.
+    pub fn require_u64(&self, key: &str) -> Result<u64, LoadError> {
+        self.require(key, "an unsigned integer", Value::as_u64)
+    }
+
+    /// Gets a required number parameter.
+    pub fn require_f64(&self, key: &str) -> Result<f64, LoadError> {
+        self.require(key, "a number", Value::as_f64)
+    }
+
+    /// Gets a required duration parameter, given as a number of seconds.
+    pub fn require_duration(&self, key: &str) -> Result<Duration, LoadError> {
+        self.optional_duration(key)?
+            .ok_or_else(|| self.missing(key))
+    }
+
+    /// Gets an optional duration parameter, given as a number of seconds.
+    pub fn optional_duration(&self, key: &str) -> Result<Option<Duration>, LoadError> {
+        self.optional(key, "a non-negative number of seconds", |v| {
+            v.as_f64().and_then(|s| Duration::try_from_secs_f64(s).ok())
+        })
+    }
+
+    /// Gets an optional boolean parameter.
+    pub fn optional_bool(&self, key: &str) -> Result<Option<bool>, LoadError> {
+        self.optional(key, "a boolean", Value::as_bool)
+    }
+
+    /// Gets a required string parameter.
+    pub fn require_str(&self, key: &str) -> Result<&'d str, LoadError> {
+        self.require(key, "a string", Value::as_str)
+    }
+}
+
+/// A function that constructs a node from its parameters and children.
+type Factory<'a, W> = Box<dyn Fn(&Params, Vec<Node<'a, W>>) -> Result<Node<'a, W>, LoadError> + 'a>;
+
+/// A map from node type names to the factories that construct them.
+pub struct Registry<'a, W> {
+    /// The registered factories along with the children they accept.
+    factories: HashMap<String, (Arity, Factory<'a, W>)>,
+}
+impl<'a, W> Registry<'a, W>
+where
+    W: 'a,
+{
+    /// Creates a registry without any node types.
+    pub fn new() -> Registry<'a, W> {
+        Registry {
+            factories: HashMap::new(),
+        }
+    }
+
+    /// Creates a registry that knows about the standard nodes.
+    ///
+    /// The following node types and parameters are registered:
+    ///
+    /// * `Sequence`, `ActiveSequence`, `Selector` and `StatefulSelector`
+    /// * `Parallel` with the required `required_successes`
+    /// * `Invert`
+    /// * `Delay` with the required `delay` in seconds
+    /// * `Cooldown` with the required `cooldown` in seconds and an optional
+    ///   `clear_on_reset`
+    /// * `Repeat`, `UntilFail` and `UntilSuccess` with an optional `limit`
+    /// * `Retry` with the required `retries`, and the optional `delay` and
+    ///   `max_delay` in seconds, `factor`, `jitter` and `seed` of its
+    ///   `Backoff`
+    /// * `StatusMap` with an optional `running`, `succeeded` and `failed`,
+    ///   each the name of the status to return instead, such as `"Failed"`
+    /// * `Throttle` with the required `period` in seconds and an optional
+    ///   `refresh_on_reset`
+    /// * `Timeout` with the required `timeout` in seconds
+    /// * `AlwaysFail`, with an optional child and an optional `reason`
+    /// * `AlwaysSucceed`, with an optional child
+    /// * `AlwaysRunning`
+    /// * `Wait` with either a `duration` in seconds or a number of `ticks`
+    pub fn with_std_nodes() -> Registry<'a, W> {
+        let mut registry = Registry::new();
+        registry
+            .register("Sequence", Arity::Any, |_, c| Ok(Sequence::new(c)))
+            .register("ActiveSequence", Arity::Any, |_, c| {
+                Ok(ActiveSequence::new().with_children(c).into_node())
+            })
+            .register("Selector", Arity::Any, |_, c| Ok(Selector::new(c)))
+            .register("StatefulSelector", Arity::Any, |_, c| {
+                Ok(StatefulSelector::new(c))
+            })
+            .register("Parallel", Arity::Any, |p, c| {
+                let required = p.require("required_successes", "an unsigned integer", |v| {
+                    v.as_u64().and_then(|n| usize::try_from(n).ok())
+                })?;
+                Ok(Parallel::new(required, c))
+            })
+            .register("Cooldown", Arity::Exactly(1), |p, mut c| {
+                let cooldown = p.require_duration("cooldown")?;
+                let clear = p.optional_bool("clear_on_reset")?.unwrap_or(false);
+                Ok(Cooldown::build(cooldown, clear, c.remove(0)))
+            })
+            .register("Delay", Arity::Exactly(1), |p, mut c| {
+                Ok(Delay::new(p.require_duration("delay")?, c.remove(0)))
+            })
+            .register("Invert", Arity::Exactly(1), |_, mut c| {
+                Ok(Invert::new(c.remove(0)))
+            })
+            .register("Repeat", Arity::Exactly(1), |p, mut c| {
+                Ok(match p.optional_u32("limit")? {
+                    Some(limit) => Repeat::with_limit(limit, c.remove(0)),
+                    None => Repeat::new(c.remove(0)),
+                })
+            })
+            .register("Retry", Arity::Exactly(1), |p, mut c| {
+                let retries = p
+                    .optional_u32("retries")?
+                    .ok_or_else(|| p.missing("retries"))?;
+                let delay = p.optional_duration("delay")?.unwrap_or_default();
+                let factor = p.optional("factor", "a number of at least 1", |v| {
+                    v.as_f64().filter(|f| f.is_finite() && *f >= 1.0)
+                })?;
+                let mut backoff = Backoff::exponential(delay, factor.unwrap_or(1.0));
+                if let Some(max) = p.optional_duration("max_delay")? {
+                    backoff = backoff.with_max(max);
+                }
+                if let Some(jitter) = p.optional("jitter", "a number from 0 to 1", |v| {
+                    v.as_f64().filter(|j| (0.0..=1.0).contains(j))
+                })? {
+                    backoff = backoff.with_jitter(jitter);
+                }
+                if let Some(seed) = p.optional("seed", "an unsigned integer", Value::as_u64)? {
+                    backoff = backoff.with_seed(seed);
+                }
+                Ok(Retry::with_backoff(retries, backoff, c.remove(0)))
+            })
+            .register("StatusMap", Arity::Exactly(1), |p, mut c| {
+                let mut table = Vec::new();
+                for &(key, from) in &[
+                    ("running", Status::Running),
+                    ("succeeded", Status::Succeeded),
+                    ("failed", Status::Failed),
+                ] {
+                    let to = p.optional(key, "\"Running\", \"Succeeded\" or \"Failed\"", |v| {
+                        v.as_str().and_then(|s| s.parse().ok())
+                    })?;
+                    if let Some(to) = to {
+                        table.push((from, to));
+                    }
+                }
+                Ok(StatusMap::new(&table, c.remove(0)))
+            })
+            .register("Throttle", Arity::Exactly(1), |p, mut c| {
+                let period = p.require_duration("period")?;
+                let refresh = p.optional_bool("refresh_on_reset")?.unwrap_or(false);
+                Ok(Throttle::build(period, refresh, c.remove(0)))
+            })
+            .register("Timeout", Arity::Exactly(1), |p, mut c| {
+                Ok(Timeout::new(p.require_duration("timeout")?, c.remove(0)))
+            })
+            .register("UntilFail", Arity::Exactly(1), |p, mut c| {
+                Ok(match p.optional_u32("limit")? {
+                    Some(limit) => UntilFail::with_limit(limit, c.remove(0)),
+                    None => UntilFail::new(c.remove(0)),
+                })
+            })
+            .register("UntilSuccess", Arity::Exactly(1), |p, mut c| {
+                Ok(match p.optional_u32("limit")? {
+                    Some(limit) => UntilSuccess::with_limit(limit, c.remove(0)),
+                    None => UntilSuccess::new(c.remove(0)),
+                })
+            })
+            .register("AlwaysFail", Arity::AtMost(1), |p, mut c| {
+                let reason = p.optional("reason", "a string", Value::as_str)?;
+                Ok(AlwaysFail::from_parts(c.pop(), reason.map(FailureReason::new)))
+            })
+            .register("AlwaysSucceed", Arity::AtMost(1), |_, mut c| {
+                Ok(match c.pop() {
+                    Some(child) => AlwaysSucceed::with_child(child),
+                    None => AlwaysSucceed::new(),
+                })
+            })
+            .register("AlwaysRunning", Arity::Exactly(0), |_, _| {
+                Ok(AlwaysRunning::new())
+            })
+            .register("Wait", Arity::Exactly(0), |p, _| {
+                match (p.optional_duration("duration")?, p.optional_u32("ticks")?) {
+                    (Some(duration), None) => Ok(Wait::new(duration)),
+                    (None, Some(ticks)) => Ok(Wait::ticks(ticks)),
+                    (None, None) => Err(p.missing("duration")),
+                    (Some(_), Some(_)) => {
+                        Err(p.invalid("ticks", "absent when there is a duration"))
+                    }
+                }
+            });
+
+        registry
+    }
+
+    /// Registers a factory for the given node type, replacing any previous
+    /// factory for that type.
+    ///
+    /// The factory is only called with a number of children that `arity`
+    /// accepts. The name of the node is applied after the factory returns.
+    /// A parameter that the factory did not look up is an error, so the
+    /// factory should read every parameter it accepts.
+    pub fn register<S, F>(&mut self, type_name: S, arity: Arity, factory: F) -> &mut Self
+    where
+        S: Into<String>,
+        F: Fn(&Params, Vec<Node<'a, W>>) -> Result<Node<'a, W>, LoadError> + 'a,
+    {
+        self.factories
+            .insert(type_name.into(), (arity, Box::new(factory)));
+        self
+    }
+
+    /// Returns `true` if there is a factory for the given node type.
+    pub fn contains(&self, type_name: &str) -> bool {
+        self.factories.contains_key(type_name)
+    }
+
+    /// Constructs a node, and all of its children, from a description.
+    pub fn build(&self, desc: &NodeDesc) -> Result<Node<'a, W>, LoadError> {
+        let (arity, factory) = self.factories.get(&desc.type_name).ok_or_else(|| {
+            LoadError::new(
+                desc.position,
+                LoadErrorKind::UnknownType(desc.type_name.clone()),
+            )
+        })?;
+
+        if !arity.accepts(desc.children.len()) {
+            return Err(LoadError::new(
+                desc.position,
+                LoadErrorKind::ChildCount {
+                    type_name: desc.type_name.clone(),
+                    expected: *arity,
+                    found: desc.children.len(),
+                },
+            ));
+        }
+
+        let children = desc
+            .children
+            .iter()
+            .map(|c| self.build(c))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let params = Params::new(desc);
+        let node = factory(&params, children)?;
+        params.check_unused()?;
+        Ok(match desc.name {
+            Some(ref name) => node.named(Some(name.clone())),
+            None => node,
+        })
+    }
+
+    /// Parses a description and constructs a behavior tree from it.
+    pub fn load(&self, text: &str) -> Result<BehaviorTree<'a, W>, LoadError> {
+        let desc = NodeDesc::parse(text)?;
+        self.build(&desc).map(BehaviorTree::new)
+    }
+
+    /// Reads a description from a file and constructs a behavior tree from it.
+    pub fn load_file<P: AsRef<Path>>(&self, path: P) -> Result<BehaviorTree<'a, W>, LoadError> {
+        let text =
+            fs::read_to_string(path).map_err(|e| LoadError::new(None, LoadErrorKind::Io(e)))?;
+        self.load(&text)
+    }
+}
+impl<'a, W> Registry<'a, W>
+where
+    W: HasBlackboard + 'a,
+{
+    /// Registers the `SubTree` node.
+    ///
+    /// The optional `remap` parameter is an object mapping local names to
+    /// names in the enclosing scope.
+    pub fn register_subtree(&mut self) -> &mut Self {
+        self.register("SubTree", Arity::Exactly(1), |p, mut c| {
+            let remap = p.optional("remap", "an object of strings", Value::as_object)?;
+            let mut pairs = Vec::new();
+            for (local, parent) in remap.unwrap_or(&[]) {
+                let parent = parent
+                    .as_str()
+                    .ok_or_else(|| p.invalid("remap", "an object of strings"))?;
+                pairs.push((local.as_str(), parent));
+            }
+            Ok(SubTree::with_remap(&pairs, c.remove(0)))
+        })
+    }
+}
+impl<'a, W> Default for Registry<'a, W>
+where
+    W: 'a,
+{
+    fn default() -> Self {
+        Registry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackboard::{Blackboard, Key};
+    use crate::status::Status;
+
+    /// Loads a tree that is expected to be invalid.
+    fn load_err(text: &str) -> LoadError {
+        match Registry::<()>::with_std_nodes().load(text) {
+            Ok(_) => panic!("Tree should not have loaded"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn load_std_nodes() {
+        let registry = Registry::with_std_nodes();
+        let mut tree = registry
+            .load(
+                r#"{
+                    "type": "Sequence",
+                    "name": "root",
+                    "children": [
+                        { "type": "Repeat", "params": { "limit": 2 }, "children": [
+                            { "type": "AlwaysFail" }
+                        ] },
+                        { "type": "Parallel", "params": { "required_successes": 1 }, "children": [
+                            { "type": "AlwaysSucceed" },
+                            { "type": "AlwaysRunning" }
+                        ] }
+                    ]
+                }"#,
+            )
+            .unwrap();
+
+        assert_eq!(tree.root().name(), "root");
+        assert_eq!(tree.root().children()[1].type_name(), "Parallel");
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        assert_eq!(tree.tick(&mut ()), Status::Succeeded);
+    }
+
+    #[test]
+    fn round_trip() {
+        let root: Node<()> = Sequence::new(vec![
+            Repeat::with_limit(2, AlwaysFail::new()),
+            UntilSuccess::new(Invert::new(AlwaysSucceed::with_child(AlwaysRunning::new()))),
+            Timeout::new(Duration::from_millis(1500), AlwaysFail::with_reason("Stuck")),
+            Retry::new(2, AlwaysFail::new()),
+            Throttle::new(Duration::from_millis(500), AlwaysSucceed::new()),
+            StatusMap::new(
+                &[
+                    (Status::Running, Status::Failed),
+                    (Status::Failed, Status::Succeeded),
+                ],
+                AlwaysRunning::new(),
+            ),
+            Throttle::refresh_on_reset(Duration::from_secs(1), AlwaysFail::new()),
+            Cooldown::new(Duration::from_secs(3), AlwaysSucceed::new()),
+            Cooldown::clear_on_reset(Duration::from_millis(10), AlwaysSucceed::new()),
+            Delay::new(Duration::from_secs(2), Wait::new(Duration::from_millis(750))),
+            Wait::ticks(3),
+            Retry::with_backoff(
+                3,
+                Backoff::exponential(Duration::from_millis(250), 2.0)
+                    .with_max(Duration::from_secs(2))
+                    .with_jitter(0.1)
+                    .with_seed(42),
+                AlwaysFail::new(),
+            ),
+            Parallel::new(1, vec![AlwaysSucceed::new(), AlwaysRunning::new()]).named(Some("p")),
+            StatefulSelector::new(vec![]),
+            ActiveSequence::new().into_node(),
+        ]);
+        let text = root.describe().to_string();
+
+        let tree = Registry::<()>::with_std_nodes().load(&text).unwrap();
+        assert_eq!(tree.describe().to_string(), text);
+    }
+
+    #[test]
+    fn unknown_type() {
+        let err =
+            load_err("{ \"type\": \"Invert\", \"children\": [\n  { \"type\": \"Nope\" }\n] }");
+        assert_eq!(err.position(), Some(Position { line: 2, column: 3 }));
+        assert_eq!(err.to_string(), "2:3: Unknown node type \"Nope\"");
+    }
+
+    #[test]
+    fn wrong_child_count() {
+        let err = load_err(
+            r#"{ "type": "Invert", "children": [
+                { "type": "AlwaysFail" }, { "type": "AlwaysFail" }
+            ] }"#,
+        );
+        assert_eq!(
+            err.to_string(),
+            "1:1: \"Invert\" expects exactly 1 child, found 2"
+        );
+    }
+
+    #[test]
+    fn parameter_errors() {
+        let err = load_err("{ \"type\": \"Parallel\" }");
+        assert_eq!(
+            err.to_string(),
+            "1:1: \"Parallel\" is missing parameter \"required_successes\""
+        );
+
+        let err = load_err(
+            r#"{ "type": "Repeat", "params": { "limit": -1 }, "children": [{ "type": "AlwaysFail" }] }"#,
+        );
+        match *err.kind() {
+            LoadErrorKind::InvalidParameter { ref param, .. } => assert_eq!(param, "limit"),
+            ref k => panic!("Unexpected error {:?}", k),
+        }
+
+        let err = load_err(
+            r#"{ "type": "Timeout", "params": { "timeout": 1e20 }, "children": [{ "type": "AlwaysFail" }] }"#,
+        );
+        assert_eq!(
+            err.to_string(),
+            "1:1: Parameter \"timeout\" of \"Timeout\" must be a non-negative number of seconds"
+        );
+
+        let err = load_err(
+            r#"{ "type": "Retry", "params": { "retries": 2, "jiter": 0.5 }, "children": [{ "type": "AlwaysFail" }] }"#,
+        );
+        assert_eq!(
+            err.to_string(),
+            "1:1: \"Retry\" has no parameter \"jiter\""
+        );
+
+        let err = load_err(
+            r#"{ "type": "StatusMap", "params": { "running": "Done" }, "children": [{ "type": "AlwaysFail" }] }"#,
+        );
+        match *err.kind() {
+            LoadErrorKind::InvalidParameter { ref param, .. } => assert_eq!(param, "running"),
+            ref k => panic!("Unexpected error {:?}", k),
+        }
+    }
+
+    #[test]
+    fn custom_and_subtree() {
+        let mut registry = Registry::<Blackboard>::with_std_nodes();
+        registry
+            .register_subtree()
+            .register("HasGoal", Arity::Exactly(0), |_, _| {
+                Ok(Condition::with_key(Key::<u32>::new("goal"), |_| true))
+            });
+
+        let mut tree = registry
+            .load(
+                r#"{ "type": "SubTree", "params": { "remap": { "goal": "target" } }, "children": [
+                    { "type": "HasGoal" }
+                ] }"#,
+            )
+            .unwrap();
+
+        let mut board = Blackboard::new();
+        assert_eq!(tree.tick(&mut board), Status::Failed);
+        board.set(&Key::new("target"), 1u32);
+        assert_eq!(tree.tick(&mut board), Status::Succeeded);
+    }
+}
@@ -1,7 +1,38 @@
 //! Behavior tree nodes and internal node logic.
 
+use crate::context::Context;
+use crate::failure::FailureReason;
+use crate::format::{NodeDesc, Value};
+use crate::observer::{NodeInfo, TreeObserver};
+use crate::pretty::Pretty;
+use crate::profile::NodeStats;
+use crate::progress::Progress;
 use crate::status::Status;
 use std::fmt;
+use std::rc::Rc;
+
+/// Identifies a node within a `BehaviorTree`.
+///
+/// Every node is given an ID when the tree is created. IDs are small integers
+/// assigned in depth-first order, starting at zero for the root.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+impl NodeId {
+    /// Creates an ID from its index.
+    pub(crate) fn new(index: usize) -> NodeId {
+        NodeId(index)
+    }
+
+    /// Returns the ID as an index, which is unique within the tree.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
 
 /// Represents a generic node.
 ///
@@ -23,6 +54,24 @@ pub struct Node<'a, W> {
     ///
     /// If present, it will be used instead of the type name.
     name: Option<String>,
+
+    /// State shared with the rest of the tree, if this node is in a tree.
+    context: Option<Rc<Context>>,
+
+    /// The path of this node from the root, if this node is in a tree.
+    path: Option<String>,
+
+    /// The ID of this node, if this node is in a tree.
+    id: Option<NodeId>,
+
+    /// The latest progress reported while this node has been running.
+    progress: Option<Progress>,
+
+    /// Why this node failed, if it failed with a reason.
+    failure: Option<FailureReason>,
+
+    /// The statistics of this node's ticks, if it has been profiled.
+    stats: Option<NodeStats>,
 }
 impl<'a, W> Node<'a, W> {
     /// Creates a new `Node` with the given `Tickable`.
@@ -36,6 +85,12 @@ impl<'a, W> Node<'a, W> {
             status: None,
             internals: Box::new(internals),
             name: None,
+            context: None,
+            path: None,
+            id: None,
+            progress: None,
+            failure: None,
+            stats: None,
         }
     }
 
@@ -46,6 +101,42 @@ impl<'a, W> Node<'a, W> {
         self.status
     }
 
+    /// Returns the latest progress the node reported while it has been
+    /// running.
+    ///
+    /// This is cleared when the node finishes, is reset, or is halted.
+    pub fn progress(&self) -> Option<&Progress> {
+        self.progress.as_ref()
+    }
+
+    /// Returns why the node failed.
+    ///
+    /// This is only set while the status of the node is `Failed`. The reason
+    /// is either given by the node itself or passed on from a failed child.
+    /// If several children failed, the reason of the last one is used.
+    pub fn failure_reason(&self) -> Option<&FailureReason> {
+        self.failure.as_ref()
+    }
+
+    /// Returns the timing statistics of the node.
+    ///
+    /// This is `None` unless the node has been ticked while profiling was
+    /// turned on for its tree. See the `profile` module.
+    pub fn stats(&self) -> Option<&NodeStats> {
+        self.stats.as_ref()
+    }
+
+    /// Forgets the timing statistics of this node and its descendants.
+    pub(crate) fn clear_stats(&mut self) {
+        self.stats = None;
+        self.internals.for_each_child_mut(&mut |c| c.clear_stats());
+    }
+
+    /// Overwrites the status of the node without ticking it.
+    pub(crate) fn set_status(&mut self, status: Option<Status>) {
+        self.status = status;
+    }
+
     /// Returns the name of this node.
     ///
     /// Unless this node was renamed via the `named` method, this will be the
@@ -58,6 +149,58 @@ impl<'a, W> Node<'a, W> {
         }
     }
 
+    /// Returns the path of this node from the root of its tree.
+    ///
+    /// This is `None` until the node becomes part of a `BehaviorTree`. See
+    /// the `observer` module for the format of the path.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Returns the ID of this node within its tree.
+    ///
+    /// This is `None` until the node becomes part of a `BehaviorTree`. IDs
+    /// are assigned in depth-first order starting from zero at the root, so
+    /// the same tree structure always gets the same IDs.
+    pub fn id(&self) -> Option<NodeId> {
+        self.id
+    }
+
+    /// Returns the descendant of this node, or this node itself, with the
+    /// given path.
+    ///
+    /// Paths are only assigned to nodes in a `BehaviorTree`, so this always
+    /// returns `None` for a node that is not part of one.
+    pub fn find(&self, path: &str) -> Option<&Node<'_, W>> {
+        let own = self.path.as_deref()?;
+        if own == path {
+            return Some(self);
+        }
+
+        // Only nodes below this one have paths that start with its own
+        if !path.strip_prefix(own)?.starts_with('/') {
+            return None;
+        }
+
+        self.children().into_iter().find_map(|c| c.find(path))
+    }
+
+    /// Returns the descendant of this node, or this node itself, with the
+    /// given ID.
+    pub fn find_id(&self, id: NodeId) -> Option<&Node<'_, W>> {
+        if self.id == Some(id) {
+            return Some(self);
+        }
+
+        // IDs are assigned depth-first, so the node can only be below the
+        // last child whose ID is not greater than the one being looked for
+        self.children()
+            .into_iter()
+            .take_while(|c| c.id.is_some_and(|c| c <= id))
+            .last()
+            .and_then(|c| c.find_id(id))
+    }
+
     /// Sets the name for this particular node.
     pub fn named<T: Into<String>>(mut self, name: Option<T>) -> Node<'a, W> {
         // We consume the node and return it to fit better into the current
@@ -72,31 +215,176 @@ impl<'a, W> Node<'a, W> {
         self.name = new_name;
         self
     }
+
+    /// Returns a description of this node and all of its children.
+    ///
+    /// The description contains the type name, the name if the node was
+    /// renamed, and the parameters reported by `Tickable::params`. Written out
+    /// with its `Display` implementation, it can be read by a
+    /// `loader::Registry` that knows the node types involved.
+    pub fn describe(&self) -> NodeDesc {
+        let mut desc = NodeDesc::new(self.type_name());
+        desc.name = self.name.clone();
+        desc.params = self.internals.params();
+        desc.children = self.children().iter().map(|c| c.describe()).collect();
+        desc
+    }
+
+    /// Makes this node and all of its descendants part of a tree.
+    ///
+    /// `next_id` is the ID given to this node and is advanced past the IDs of
+    /// all of its descendants.
+    pub(crate) fn attach_tree(&mut self, context: &Rc<Context>, path: String, next_id: &mut usize) {
+        self.id = Some(NodeId(*next_id));
+        *next_id += 1;
+
+        // Siblings that share a name are told apart by an index
+        let names: Vec<String> = self
+            .children()
+            .iter()
+            .map(|c| path_segment(c.name()))
+            .collect();
+        let mut segments = Vec::with_capacity(names.len());
+        for (i, name) in names.iter().enumerate() {
+            if names.iter().filter(|n| *n == name).count() > 1 {
+                let index = names[..i].iter().filter(|n| *n == name).count();
+                segments.push(format!("{}/{}[{}]", path, name, index));
+            } else {
+                segments.push(format!("{}/{}", path, name));
+            }
+        }
+
+        let mut segments = segments.into_iter();
+        let mut visited = 0;
+        self.internals.for_each_child_mut(&mut |child| {
+            let child_path = segments.next().expect("Children changed while attaching");
+            child.attach_tree(context, child_path, next_id);
+            visited += 1;
+        });
+        debug_assert_eq!(
+            visited,
+            names.len(),
+            "{} does not visit all of its children in for_each_child_mut",
+            self.internals.type_name()
+        );
+
+        self.internals.attach(context);
+        self.context = Some(context.clone());
+        self.path = Some(path);
+    }
+
+    /// Finds the reason for a failure on the tick that just finished.
+    fn find_failure(&mut self) -> Option<FailureReason> {
+        if let Some(reason) = self.internals.take_failure() {
+            return Some(reason.or_origin(self.path.as_deref()));
+        }
+
+        self.children()
+            .into_iter()
+            .rev()
+            .filter(|c| c.status() == Some(Status::Failed))
+            .find_map(|c| c.failure_reason().cloned())
+    }
+
+    /// Calls the given function on every observer of the tree.
+    fn notify<F>(&self, mut f: F)
+    where
+        F: FnMut(&mut dyn TreeObserver, &NodeInfo),
+    {
+        if let (Some(context), Some(path), Some(id)) = (&self.context, &self.path, self.id) {
+            let info = NodeInfo {
+                id,
+                path,
+                name: self.name(),
+                type_name: self.type_name(),
+                failure_reason: self.failure.as_ref(),
+            };
+            context.notify(|o| f(o, &info));
+        }
+    }
 }
 
 impl<'a, W> Tickable<W> for Node<'a, W> {
     /// Ticks the node a single time.
     fn tick(&mut self, world: &mut W) -> Status {
-        
+        self.notify(|o, n| o.on_tick_start(n));
+
         // Tick the internals
         trace!("Ticking node {}", self.name());
-        self.status = Some(self.internals.tick(world));
-        return self.status.unwrap();
+        let old = self.status;
+        let clock = self
+            .context
+            .as_ref()
+            .filter(|c| c.profiling())
+            .map(|c| c.clock());
+        let start = clock.as_ref().map(|c| c.now());
+        let status = self.internals.tick(world);
+        self.status = Some(status);
+        if let (Some(clock), Some(start)) = (clock, start) {
+            let stats = self.stats.get_or_insert_with(NodeStats::default);
+            stats.record(clock.now().saturating_duration_since(start), status);
+        }
+
+        if let Some(message) = self.internals.take_panic() {
+            self.notify(|o, n| o.on_panic(n, &message));
+        }
+        if let Some(progress) = self.internals.take_progress() {
+            self.notify(|o, n| o.on_progress(n, &progress));
+            self.progress = Some(progress);
+        }
+        if status != Status::Running {
+            self.progress = None;
+        }
+        self.failure = if status == Status::Failed {
+            self.find_failure()
+        } else {
+            None
+        };
+
+        if old != self.status {
+            self.notify(|o, n| o.on_status_change(n, old, status));
+        }
+
+        status
     }
 
     /// Resets the node.
     ///
     /// This returns the node to a state that is identical to when it was first
     /// created. If the node state is still `Initialized`, then the internal
-    /// reset method will not be called.
+    /// reset method will not be called. If the node is running, it is halted
+    /// instead.
     fn reset(&mut self) {
-        if self.status.is_some() {
+        if self.status == Some(Status::Running) {
+            self.halt();
+        } else if self.status.is_some() {
             trace!("Resetting node {} ({:?})", self.name(), self.status());
+            self.notify(|o, n| o.on_reset(n));
             self.status = None;
+            self.progress = None;
+            self.failure = None;
             self.internals.reset();
         }
     }
 
+    /// Halts the node.
+    ///
+    /// If the node is running, the internal halt method is called to stop it
+    /// and observers are told that the node was halted. Otherwise, this is the
+    /// same as `reset`. Either way, the node is left in its initial state.
+    fn halt(&mut self) {
+        if self.status == Some(Status::Running) {
+            trace!("Halting node {}", self.name());
+            self.notify(|o, n| o.on_halt(n));
+            self.status = None;
+            self.progress = None;
+            self.failure = None;
+            self.internals.halt();
+        } else {
+            self.reset();
+        }
+    }
+
     /// Returns a vector containing references to all of this node's children.
     ///
     /// This is likely the most unstable part of Aspen, use with caution.
@@ -104,11 +392,41 @@ impl<'a, W> Tickable<W> for Node<'a, W> {
         (*self.internals).children()
     }
 
+    /// Calls the function on each of this node's children.
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        self.internals.for_each_child_mut(f)
+    }
+
     /// Returns the type of the node as a string literal
     fn type_name(&self) -> &str {
         self.internals.type_name()
     }
 
+    /// Does nothing, as a `Node` attaches its internals itself.
+    fn attach(&mut self, _context: &Rc<Context>) {}
+
+    /// Returns nothing, as a `Node` takes the progress of its internals
+    /// itself. Use `Node::progress` instead.
+    fn take_progress(&mut self) -> Option<Progress> {
+        None
+    }
+
+    /// Returns nothing, as a `Node` passes the panics of its internals on to
+    /// the observers itself.
+    fn take_panic(&mut self) -> Option<String> {
+        None
+    }
+
+    /// Returns the reason the node failed, if any.
+    fn take_failure(&mut self) -> Option<FailureReason> {
+        self.failure.clone()
+    }
+
+    /// Returns the parameters of the node's internals.
+    fn params(&self) -> Vec<(String, Value)> {
+        self.internals.params()
+    }
+
     /// Returns a concrete Node.
     ///
     /// (Node.into_node() does precisely nothing)
@@ -121,8 +439,19 @@ impl<'a, W> Tickable<W> for Node<'a, W> {
 }
 
 impl<'a, W> fmt::Display for Node<'a, W> {
+    /// Writes the node and its children on a single line.
+    ///
+    /// The alternate format (`{:#}`) instead writes an indented tree with one
+    /// node per line. See `pretty::Pretty` for more options.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return Pretty::new(self).fmt(f);
+        }
+
         write!(f, "{}:( status = {:?}", self.name(), self.status())?;
+        if let Some(ref reason) = self.failure {
+            write!(f, ", reason = {:?}", reason.to_string())?;
+        }
         for child in self.children() {
             write!(f, ", {}", child)?;
         }
@@ -130,6 +459,23 @@ impl<'a, W> fmt::Display for Node<'a, W> {
     }
 }
 
+/// Returns the name of a node as it appears in a path.
+///
+/// The separator `/`, the `[` that starts an index and the escape character
+/// itself are escaped with a backslash, so that every node in a tree has a
+/// different path no matter how it is named.
+pub(crate) fn path_segment(name: &str) -> String {
+    let mut segment = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c == '\\' || c == '/' || c == '[' {
+            segment.push('\\');
+        }
+        segment.push(c);
+    }
+
+    segment
+}
+
 /// The internal logic of a node.
 ///
 /// This is the object that controls the tick behavior of the `Node`, with
@@ -151,6 +497,21 @@ pub trait Tickable<W> {
     /// node. Note that this could be called when the node is in any state.
     fn reset(&mut self);
 
+    /// Stops the node while it is running and resets its internal state.
+    ///
+    /// This is called instead of `reset` when the node is interrupted before
+    /// it finished, either because a parent preempted it or because the tree
+    /// was reset. Nodes that do work outside of their ticks should use it to
+    /// stop that work. Composite nodes call it on running children that they
+    /// no longer want to tick.
+    ///
+    /// The default behavior is to call `reset`, which is suitable for nodes
+    /// that do all of their work while being ticked. `Node::reset` halts any
+    /// running children, so composite nodes do not need to implement this.
+    fn halt(&mut self) {
+        self.reset();
+    }
+
     /// Returns a vector of references to this node's children.
     ///
     /// Default behavior is to return `None`, which should be suitable for any
@@ -161,6 +522,52 @@ pub trait Tickable<W> {
         Vec::with_capacity(0)
     }
 
+    /// Calls the given function on each of this node's children, in the same
+    /// order as `children`.
+    ///
+    /// Default behavior is to do nothing, which should be suitable for any
+    /// leaf node. Composite nodes and decorators must override this to visit
+    /// every child that `children` returns, or their children are not made
+    /// part of a `BehaviorTree`: they get no path or ID, cannot be found and
+    /// are not reported to its observers. Debug builds panic when a tree is
+    /// created with a node that visits a different number of children.
+    fn for_each_child_mut(&mut self, _f: &mut dyn FnMut(&mut Node<'_, W>)) {}
+
+    /// Called when the node becomes part of a `BehaviorTree`.
+    ///
+    /// Nodes that need the services of the tree should keep the context. The
+    /// default behavior is to ignore it. Children are attached by their own
+    /// `Node`, so this must not be forwarded to them.
+    fn attach(&mut self, _context: &Rc<Context>) {}
+
+    /// Returns the progress the node reported since this was last called.
+    ///
+    /// This is called after every tick. Nodes that do long-running work can
+    /// use it to say how far along they are. The default is to report
+    /// nothing.
+    fn take_progress(&mut self) -> Option<Progress> {
+        None
+    }
+
+    /// Returns the message of a panic that was caught in the node's work
+    /// since this was last called.
+    ///
+    /// This is called after every tick and is meant for nodes that run work
+    /// on other threads, where a panic would otherwise go unnoticed. The
+    /// default is to report nothing.
+    fn take_panic(&mut self) -> Option<String> {
+        None
+    }
+
+    /// Returns why the node failed on the tick that just finished.
+    ///
+    /// This is only called after `tick` returned `Status::Failed`. The
+    /// default is to give no reason, in which case the node takes the reason
+    /// of its last failed child, if any.
+    fn take_failure(&mut self) -> Option<FailureReason> {
+        None
+    }
+
     /// Returns the type of the node as a string literal.
     ///
     /// In general, this should be the name of the node type.
@@ -168,6 +575,16 @@ pub trait Tickable<W> {
     /// node type
     fn type_name(&self) -> &str;
 
+    /// Returns the parameters that configure this node.
+    ///
+    /// These are written out by `Node::describe` and should use the same keys
+    /// that the node's factory in a `loader::Registry` reads. The default is
+    /// no parameters, which is suitable for nodes that are only configured by
+    /// their children or by closures.
+    fn params(&self) -> Vec<(String, Value)> {
+        Vec::new()
+    }
+
     /// Consumes 'self' and returns a concrete Node struct.
     ///
     /// This method is used to allow child nodes methods to
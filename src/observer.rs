@@ -0,0 +1,347 @@
+//! Hooks for watching the execution of a tree.
+//!
+//! A `TreeObserver` added to a `BehaviorTree` is told whenever a node in that
+//! tree is ticked, changes status, reports progress, is reset or is halted.
+//! Each event comes with a `NodeInfo` identifying the node by its path from
+//! the root, which stays the same for the lifetime of the tree.
+//!
+//! Paths are made of node names separated by `/`, starting with the name of
+//! the root. If several children of the same node share a name, they are told
+//! apart by their index among those siblings, e.g. `root/Action[1]`. A `/`,
+//! `[` or `\` in a name is escaped with a backslash, so that a node named
+//! `a/b` has the path `root/a\/b` and no two nodes share a path. Nodes also
+//! have a numeric `NodeId`, which is cheaper to store and compare.
+//!
+//! # Examples
+//!
+//! ```
+//! # use aspen::std_nodes::*;
+//! # use aspen::BehaviorTree;
+//! # use aspen::observer::{NodeInfo, TreeObserver};
+//! # use aspen::Status;
+//! # use std::rc::Rc;
+//! # use std::cell::RefCell;
+//! struct Log(Rc<RefCell<Vec<String>>>);
+//! impl TreeObserver for Log {
+//!     fn on_status_change(&mut self, node: &NodeInfo, _: Option<Status>, new: Status) {
+//!         self.0.borrow_mut().push(format!("{} {:?}", node.path(), new));
+//!     }
+//! }
+//!
+//! let log = Rc::new(RefCell::new(Vec::new()));
+//! let root = Sequence::new(vec![AlwaysSucceed::new(), AlwaysSucceed::new()]);
+//! let mut tree = BehaviorTree::new(root.named(Some("root")));
+//! tree.add_observer(Log(log.clone()));
+//! tree.tick(&mut ());
+//!
+//! assert_eq!(*log.borrow(), vec![
+//!     "root/AlwaysSucceed[0] Succeeded",
+//!     "root/AlwaysSucceed[1] Succeeded",
+//!     "root Succeeded",
+//! ]);
+//! ```
+use crate::failure::FailureReason;
+use crate::node::NodeId;
+use crate::progress::Progress;
+use crate::status::Status;
+
+/// Identifies the node that an event is about.
+pub struct NodeInfo<'n> {
+    /// The ID of the node.
+    pub(crate) id: NodeId,
+
+    /// The path of the node from the root of the tree.
+    pub(crate) path: &'n str,
+
+    /// The name of the node.
+    pub(crate) name: &'n str,
+
+    /// The type name of the node.
+    pub(crate) type_name: &'n str,
+
+    /// Why the node failed, if it failed with a reason.
+    pub(crate) failure_reason: Option<&'n FailureReason>,
+}
+impl<'n> NodeInfo<'n> {
+    /// Returns the ID of the node.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Returns the path of the node from the root of the tree.
+    pub fn path(&self) -> &str {
+        self.path
+    }
+
+    /// Returns the name of the node.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Returns the type name of the node.
+    pub fn type_name(&self) -> &str {
+        self.type_name
+    }
+
+    /// Returns why the node failed.
+    ///
+    /// This is only set when the node has failed with a reason, so it can be
+    /// read in `TreeObserver::on_status_change` when the new status is
+    /// `Status::Failed`.
+    pub fn failure_reason(&self) -> Option<&FailureReason> {
+        self.failure_reason
+    }
+}
+
+/// Receives events from the nodes of a `BehaviorTree`.
+///
+/// All methods have empty default implementations, so observers only need to
+/// implement the events they are interested in. Observers are called on the
+/// ticking thread while the tree is being ticked and must not block for long.
+pub trait TreeObserver {
+    /// Called when the tree starts a tick, before any node is reset or ticked.
+    ///
+    /// Ticks are numbered from one.
+    fn on_tree_tick(&mut self, _tick: u64) {}
+
+    /// Called before a node's internals are ticked.
+    fn on_tick_start(&mut self, _node: &NodeInfo) {}
+
+    /// Called after a node was ticked if the status differs from the status
+    /// it had before the tick.
+    fn on_status_change(&mut self, _node: &NodeInfo, _old: Option<Status>, _new: Status) {}
+
+    /// Called when a node that has finished is reset.
+    fn on_reset(&mut self, _node: &NodeInfo) {}
+
+    /// Called instead of `on_reset` when a node is stopped while it is still
+    /// running, either by a parent that preempted it or by a reset of the tree.
+    fn on_halt(&mut self, _node: &NodeInfo) {}
+
+    /// Called after a node was ticked if the work it runs outside of its ticks
+    /// panicked, with the message of the panic.
+    ///
+    /// This is called before `on_progress` and `on_status_change`.
+    fn on_panic(&mut self, _node: &NodeInfo, _message: &str) {}
+
+    /// Called after a node was ticked if it reported progress during the
+    /// tick, before `on_status_change`.
+    fn on_progress(&mut self, _node: &NodeInfo, _progress: &Progress) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bt::BehaviorTree;
+    use crate::node::{Node, Tickable};
+    use crate::std_nodes::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Records every event as a string.
+    struct Recorder(Rc<RefCell<Vec<String>>>);
+    impl TreeObserver for Recorder {
+        fn on_tick_start(&mut self, node: &NodeInfo) {
+            self.0.borrow_mut().push(format!("tick {}", node.path()));
+        }
+
+        fn on_status_change(&mut self, node: &NodeInfo, old: Option<Status>, new: Status) {
+            self.0
+                .borrow_mut()
+                .push(format!("{} {:?} -> {:?}", node.path(), old, new));
+        }
+
+        fn on_reset(&mut self, node: &NodeInfo) {
+            self.0.borrow_mut().push(format!("reset {}", node.path()));
+        }
+
+        fn on_halt(&mut self, node: &NodeInfo) {
+            self.0.borrow_mut().push(format!("halt {}", node.path()));
+        }
+    }
+
+    #[test]
+    fn events() {
+        let root = Sequence::new(vec![
+            Invert::new(AlwaysFail::new()).named(Some("check")),
+            AlwaysRunning::new(),
+        ]);
+        let mut tree = BehaviorTree::new(root);
+        let log = Rc::new(RefCell::new(Vec::new()));
+        tree.add_observer(Recorder(log.clone()));
+
+        tree.tick(&mut ());
+        tree.tick(&mut ());
+        tree.reset();
+
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                "tick Sequence",
+                "tick Sequence/check",
+                "tick Sequence/check/AlwaysFail",
+                "Sequence/check/AlwaysFail None -> Failed",
+                "Sequence/check None -> Succeeded",
+                "tick Sequence/AlwaysRunning",
+                "Sequence/AlwaysRunning None -> Running",
+                "Sequence None -> Running",
+                "tick Sequence",
+                "tick Sequence/AlwaysRunning",
+                "halt Sequence",
+                "reset Sequence/check",
+                "reset Sequence/check/AlwaysFail",
+                "halt Sequence/AlwaysRunning",
+            ]
+        );
+    }
+
+    #[test]
+    fn failure_reasons() {
+        struct Failures(Rc<RefCell<Vec<String>>>);
+        impl TreeObserver for Failures {
+            fn on_status_change(&mut self, node: &NodeInfo, _: Option<Status>, _: Status) {
+                if let Some(reason) = node.failure_reason() {
+                    self.0
+                        .borrow_mut()
+                        .push(format!("{} {}", node.path(), reason));
+                }
+            }
+        }
+
+        let root = Sequence::new(vec![
+            AlwaysSucceed::new(),
+            Invert::new(AlwaysFail::with_reason("Unused")),
+            InlineAction::fallible(|_| Err("Gripper is stuck")).named(Some("grab")),
+        ]);
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut tree = BehaviorTree::new(root.named(Some("root")));
+        tree.add_observer(Failures(log.clone()));
+
+        // Reasons of children that did not cause the failure are not passed on
+        assert_eq!(tree.tick(&mut ()), Status::Failed);
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                "root/Invert/AlwaysFail root/Invert/AlwaysFail: Unused",
+                "root/grab root/grab: Gripper is stuck",
+                "root root/grab: Gripper is stuck",
+            ]
+        );
+        assert_eq!(
+            tree.root().to_string(),
+            concat!(
+                "root:( status = Some(Failed), reason = \"root/grab: Gripper is stuck\", ",
+                "AlwaysSucceed:( status = Some(Succeeded) ), ",
+                "Invert:( status = Some(Succeeded), ",
+                "AlwaysFail:( status = Some(Failed), reason = \"root/Invert/AlwaysFail: Unused\" ) ), ",
+                "grab:( status = Some(Failed), reason = \"root/grab: Gripper is stuck\" ) )"
+            )
+        );
+
+        tree.reset();
+        assert!(tree.root().failure_reason().is_none());
+    }
+
+    #[test]
+    fn paths_and_ids() {
+        let root: Node<()> = Selector::new(vec![
+            AlwaysFail::new(),
+            Invert::new(AlwaysSucceed::new()),
+            AlwaysFail::new(),
+        ]);
+        let tree = BehaviorTree::new(root.named(Some("root")));
+        let children = tree.root().children();
+
+        assert_eq!(tree.root().path(), Some("root"));
+        assert_eq!(children[0].path(), Some("root/AlwaysFail[0]"));
+        assert_eq!(children[1].path(), Some("root/Invert"));
+        assert_eq!(
+            children[1].children()[0].path(),
+            Some("root/Invert/AlwaysSucceed")
+        );
+        assert_eq!(children[2].path(), Some("root/AlwaysFail[1]"));
+        assert_eq!(AlwaysFail::<()>::new().path(), None);
+
+        let ids: Vec<_> = [
+            "root",
+            "root/AlwaysFail[0]",
+            "root/Invert",
+            "root/Invert/AlwaysSucceed",
+            "root/AlwaysFail[1]",
+        ]
+        .iter()
+        .map(|p| tree.find(p).and_then(|n| n.id()).map(|id| id.index()))
+        .collect();
+        assert_eq!(ids, vec![Some(0), Some(1), Some(2), Some(3), Some(4)]);
+        let id = children[1].children()[0].id().unwrap();
+        assert_eq!(
+            tree.node(id).unwrap().path(),
+            Some("root/Invert/AlwaysSucceed")
+        );
+        let last = children[2].id().unwrap();
+        assert_eq!(tree.node(last).unwrap().path(), Some("root/AlwaysFail[1]"));
+        assert!(tree.find("root/Invert/AlwaysFail").is_none());
+        assert!(tree.find("root/AlwaysFail").is_none());
+        assert!(tree.find("other").is_none());
+    }
+
+    #[test]
+    fn paths_are_unique() {
+        let root: Node<()> = Sequence::new(vec![
+            AlwaysSucceed::new().named(Some("A")),
+            AlwaysSucceed::new().named(Some("A")),
+            AlwaysSucceed::new().named(Some("A[1]")),
+            AlwaysSucceed::new().named(Some("b/c")),
+            Invert::new(AlwaysFail::new().named(Some("c"))).named(Some("b")),
+        ]);
+        let tree = BehaviorTree::new(root.named(Some("root")));
+
+        let paths: Vec<_> = tree
+            .root()
+            .children()
+            .iter()
+            .map(|c| c.path().unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "root/A[0]",
+                "root/A[1]",
+                "root/A\\[1]",
+                "root/b\\/c",
+                "root/b"
+            ]
+        );
+
+        let index = |path| tree.find(path).and_then(|n| n.id()).map(|id| id.index());
+        assert_eq!(index("root/b/c"), Some(6));
+        assert_eq!(index("root/b\\/c"), Some(4));
+    }
+
+    /// A decorator that forgets to visit its child.
+    struct Forgetful(Node<'static, ()>);
+    impl Tickable<()> for Forgetful {
+        fn tick(&mut self, world: &mut ()) -> Status {
+            self.0.tick(world)
+        }
+
+        fn reset(&mut self) {
+            self.0.reset();
+        }
+
+        fn children(&self) -> Vec<&Node<'_, ()>> {
+            vec![&self.0]
+        }
+
+        fn type_name(&self) -> &'static str {
+            "Forgetful"
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Forgetful does not visit all of its children")]
+    fn unvisited_children() {
+        BehaviorTree::new(Forgetful(AlwaysSucceed::new()).into_node());
+    }
+}
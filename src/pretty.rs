@@ -0,0 +1,196 @@
+//! Multi-line printing of a tree with one node per line.
+use crate::node::{Node, Tickable};
+use crate::status::Status;
+use std::fmt;
+
+/// Prints a node and its descendants as an indented tree.
+///
+/// Each line holds a glyph for the node's status, its name, its type name if
+/// the node was renamed, the status itself, and the progress of running
+/// nodes or the reason of failed ones. This is what the alternate `{:#}`
+/// format of `Node` and `BehaviorTree` prints. By default the branches are
+/// drawn with Unicode box characters and no colors are used.
+///
+/// # Examples
+///
+/// ```
+/// # use aspen::std_nodes::*;
+/// # use aspen::node::Tickable;
+/// # use aspen::pretty::Pretty;
+/// let mut root = Sequence::new(vec![
+///     AlwaysSucceed::new(),
+///     AlwaysRunning::new().named(Some("wait")),
+///     AlwaysFail::new(),
+/// ]);
+/// root.tick(&mut ());
+///
+/// assert_eq!(
+///     Pretty::new(&root).ascii().to_string(),
+///     "~ Sequence: Running\n\
+///      |-- + AlwaysSucceed: Succeeded\n\
+///      |-- ~ wait (AlwaysRunning): Running\n\
+///      `-- - AlwaysFail: Idle\n"
+/// );
+/// ```
+pub struct Pretty<'n, 'a, W> {
+    /// The node at the top of the printed tree.
+    root: &'n Node<'a, W>,
+
+    /// Whether to draw with ASCII rather than Unicode characters.
+    ascii: bool,
+
+    /// Whether to color the status with ANSI escape codes.
+    color: bool,
+}
+impl<'n, 'a, W> Pretty<'n, 'a, W> {
+    /// Creates a printer for the given node.
+    pub fn new(root: &'n Node<'a, W>) -> Self {
+        Pretty {
+            root,
+            ascii: false,
+            color: false,
+        }
+    }
+
+    /// Draws the tree using only ASCII characters.
+    pub fn ascii(mut self) -> Self {
+        self.ascii = true;
+        self
+    }
+
+    /// Colors the status of each node using ANSI escape codes.
+    pub fn color(mut self) -> Self {
+        self.color = true;
+        self
+    }
+
+    /// Writes a single node, and then its children, to the formatter.
+    fn write_node(&self, f: &mut fmt::Formatter, node: &Node<W>, prefix: &str) -> fmt::Result {
+        let status = node.status();
+        let (glyph, text, ansi) = match status {
+            None => ("\u{25cb}", "Idle", "2"),
+            Some(Status::Running) => ("\u{25b6}", "Running", "33"),
+            Some(Status::Succeeded) => ("\u{2714}", "Succeeded", "32"),
+            Some(Status::Failed) => ("\u{2718}", "Failed", "31"),
+        };
+        let glyph = if self.ascii {
+            match status {
+                None => "-",
+                Some(Status::Running) => "~",
+                Some(Status::Succeeded) => "+",
+                Some(Status::Failed) => "x",
+            }
+        } else {
+            glyph
+        };
+
+        if self.color {
+            write!(f, "\x1b[{}m{}\x1b[0m ", ansi, glyph)?;
+        } else {
+            write!(f, "{} ", glyph)?;
+        }
+
+        write!(f, "{}", node.name())?;
+        if node.name() != node.type_name() {
+            write!(f, " ({})", node.type_name())?;
+        }
+
+        if self.color {
+            write!(f, ": \x1b[{}m{}\x1b[0m", ansi, text)?;
+        } else {
+            write!(f, ": {}", text)?;
+        }
+        match (node.progress(), node.failure_reason()) {
+            (Some(progress), _) if !progress.to_string().is_empty() => {
+                writeln!(f, " ({})", progress)?
+            }
+            (_, Some(reason)) => writeln!(f, " ({})", reason)?,
+            _ => writeln!(f)?,
+        }
+
+        let (branch, last, pipe) = if self.ascii {
+            ("|-- ", "`-- ", "|   ")
+        } else {
+            (
+                "\u{251c}\u{2500}\u{2500} ",
+                "\u{2514}\u{2500}\u{2500} ",
+                "\u{2502}   ",
+            )
+        };
+
+        let children = node.children();
+        for (i, child) in children.iter().enumerate() {
+            let is_last = i + 1 == children.len();
+            write!(f, "{}{}", prefix, if is_last { last } else { branch })?;
+            let child_prefix = format!("{}{}", prefix, if is_last { "    " } else { pipe });
+            self.write_node(f, child, &child_prefix)?;
+        }
+
+        Ok(())
+    }
+}
+impl<'n, 'a, W> fmt::Display for Pretty<'n, 'a, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_node(f, self.root, "")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bt::BehaviorTree;
+    use crate::std_nodes::*;
+
+    #[test]
+    fn unicode_nested() {
+        let mut root = Selector::new(vec![
+            Invert::new(AlwaysSucceed::new()),
+            Sequence::new(vec![AlwaysSucceed::new(), AlwaysFail::new()]),
+            AlwaysRunning::new(),
+        ]);
+        root.tick(&mut ());
+
+        assert_eq!(
+            format!("{:#}", root),
+            concat!(
+                "\u{25b6} Selector: Running\n",
+                "\u{251c}\u{2500}\u{2500} \u{2718} Invert: Failed\n",
+                "\u{2502}   \u{2514}\u{2500}\u{2500} \u{2714} AlwaysSucceed: Succeeded\n",
+                "\u{251c}\u{2500}\u{2500} \u{2718} Sequence: Failed\n",
+                "\u{2502}   \u{251c}\u{2500}\u{2500} \u{2714} AlwaysSucceed: Succeeded\n",
+                "\u{2502}   \u{2514}\u{2500}\u{2500} \u{2718} AlwaysFail: Failed\n",
+                "\u{2514}\u{2500}\u{2500} \u{25b6} AlwaysRunning: Running\n",
+            )
+        );
+    }
+
+    #[test]
+    fn failure_reason() {
+        let root = Selector::new(vec![
+            AlwaysFail::with_reason("Too far"),
+            AlwaysFail::with_reason("Too heavy").named(Some("lift")),
+        ]);
+        let mut tree = BehaviorTree::new(root);
+        tree.tick(&mut ());
+
+        assert_eq!(
+            Pretty::new(tree.root()).ascii().to_string(),
+            concat!(
+                "x Selector: Failed (Selector/lift: Too heavy)\n",
+                "|-- x AlwaysFail: Failed (Selector/AlwaysFail: Too far)\n",
+                "`-- x lift (AlwaysFail): Failed (Selector/lift: Too heavy)\n",
+            )
+        );
+    }
+
+    #[test]
+    fn color() {
+        let mut root = AlwaysFail::new();
+        root.tick(&mut ());
+
+        assert_eq!(
+            Pretty::new(&root).ascii().color().to_string(),
+            "\x1b[31mx\x1b[0m AlwaysFail: \x1b[31mFailed\x1b[0m\n"
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Timing statistics for the nodes of a tree.
+//!
+//! Profiling is off by default, as it reads the time twice for every tick of
+//! every node. The time is read from the clock of the tree, so a tree with a
+//! `MockClock` only sees the time that the clock was advanced by. Once it is turned on with `BehaviorTree::set_profiling`, each
+//! node keeps a `NodeStats` that can be read with `Node::stats`, and the tree
+//! can be summarized with `BehaviorTree::profile`.
+//!
+//! The time of a node includes the time of the children it ticked. The
+//! report subtracts that from the parent to get the time the node spent on
+//! its own, which is what the flame graph format expects.
+//!
+//! # Examples
+//!
+//! ```
+//! # use aspen::std_nodes::*;
+//! # use aspen::{BehaviorTree, Status};
+//! let root = Selector::new(vec![
+//!     Condition::new(|_| false).named(Some("check")),
+//!     AlwaysRunning::new(),
+//! ]);
+//! let mut tree = BehaviorTree::new(root.named(Some("root")));
+//! tree.set_profiling(true);
+//!
+//! for _ in 0..10 {
+//!     tree.tick(&mut ());
+//! }
+//!
+//! let stats = tree.find("root/check").unwrap().stats().unwrap();
+//! assert_eq!(stats.ticks(), 10);
+//! assert_eq!(stats.count(Status::Failed), 10);
+//!
+//! // Find the nodes that took the longest
+//! let mut profile = tree.profile();
+//! profile.sort_by_total();
+//! println!("{}", profile);
+//! ```
+use crate::node::{Node, NodeId, Tickable};
+use crate::status::Status;
+use std::cmp::Reverse;
+use std::fmt;
+use std::time::Duration;
+
+/// The timing statistics of a single node.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct NodeStats {
+    /// The number of times the node was ticked.
+    ticks: u64,
+
+    /// The time spent in all of the ticks.
+    total: Duration,
+
+    /// The shortest tick.
+    min: Duration,
+
+    /// The longest tick.
+    max: Duration,
+
+    /// The number of ticks that returned `Running`, `Succeeded` and `Failed`.
+    statuses: [u64; 3],
+}
+impl NodeStats {
+    /// Adds a tick that took the given time.
+    pub(crate) fn record(&mut self, duration: Duration, status: Status) {
+        self.min = if self.ticks == 0 {
+            duration
+        } else {
+            self.min.min(duration)
+        };
+        self.max = self.max.max(duration);
+        self.ticks += 1;
+        self.total += duration;
+        self.statuses[status_index(status)] += 1;
+    }
+
+    /// Returns the number of times the node was ticked.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Returns the time spent in all of the ticks, including the children.
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// Returns the shortest tick.
+    pub fn min(&self) -> Duration {
+        self.min
+    }
+
+    /// Returns the longest tick.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Returns the average time of a tick, or zero if there were none.
+    pub fn mean(&self) -> Duration {
+        if self.ticks == 0 {
+            Duration::default()
+        } else {
+            Duration::from_secs_f64(self.total.as_secs_f64() / self.ticks as f64)
+        }
+    }
+
+    /// Returns the number of ticks that returned the given status.
+    pub fn count(&self, status: Status) -> u64 {
+        self.statuses[status_index(status)]
+    }
+}
+
+/// Returns where the count of a status is kept.
+fn status_index(status: Status) -> usize {
+    match status {
+        Status::Running => 0,
+        Status::Succeeded => 1,
+        Status::Failed => 2,
+    }
+}
+
+/// The statistics of one node in a `Profile`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProfileEntry {
+    /// The ID of the node.
+    pub id: NodeId,
+
+    /// The path of the node from the root.
+    pub path: String,
+
+    /// The statistics of the node.
+    pub stats: NodeStats,
+
+    /// The time the node spent outside of its children.
+    pub self_time: Duration,
+}
+
+/// The statistics of every node in a tree that has been ticked.
+///
+/// The default format is a table with one node per line. The entries start
+/// out in depth-first order.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Profile {
+    /// The statistics of every node that has been ticked.
+    entries: Vec<ProfileEntry>,
+}
+impl Profile {
+    /// Collects the statistics of the given node and its descendants.
+    pub(crate) fn new<W>(root: &Node<W>) -> Profile {
+        let mut entries = Vec::new();
+        collect(root, &mut entries);
+        Profile { entries }
+    }
+
+    /// Returns the statistics of every node that has been ticked.
+    pub fn entries(&self) -> &[ProfileEntry] {
+        &self.entries
+    }
+
+    /// Orders the entries by their total time, longest first.
+    pub fn sort_by_total(&mut self) {
+        self.entries.sort_by_key(|e| Reverse(e.stats.total()));
+    }
+
+    /// Orders the entries by the time spent outside of their children,
+    /// longest first.
+    pub fn sort_by_self_time(&mut self) {
+        self.entries.sort_by_key(|e| Reverse(e.self_time));
+    }
+
+    /// Writes the profile in the folded stack format read by flame graph
+    /// tools, such as `inferno` or `flamegraph.pl`.
+    ///
+    /// Every line holds the path of a node, with `;` between the names, and
+    /// the time the node spent outside of its children in microseconds. As
+    /// those tools split lines on `;` and spaces, any of them in a name are
+    /// replaced with `_`.
+    pub fn to_folded(&self) -> String {
+        let mut out = String::new();
+        for entry in &self.entries {
+            let mut chars = entry.path.chars();
+            while let Some(c) = chars.next() {
+                // Escaped characters are part of a name, not separators
+                match c {
+                    '/' => out.push(';'),
+                    '\\' => out.push(folded_char(chars.next().unwrap_or(c))),
+                    c => out.push(folded_char(c)),
+                }
+            }
+            out.push_str(&format!(" {}\n", entry.self_time.as_micros()));
+        }
+        out
+    }
+}
+impl fmt::Display for Profile {
+    /// Writes a table with the statistics of every node, with times in
+    /// microseconds.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .entries
+            .iter()
+            .map(|e| e.path.len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+
+        writeln!(
+            f,
+            "{:<width$} {:>8} {:>10} {:>10} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            "node",
+            "ticks",
+            "total",
+            "self",
+            "mean",
+            "min",
+            "max",
+            "running",
+            "success",
+            "failure",
+            width = width
+        )?;
+        for entry in &self.entries {
+            let stats = &entry.stats;
+            writeln!(
+                f,
+                "{:<width$} {:>8} {:>10} {:>10} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                entry.path,
+                stats.ticks(),
+                stats.total().as_micros(),
+                entry.self_time.as_micros(),
+                stats.mean().as_micros(),
+                stats.min().as_micros(),
+                stats.max().as_micros(),
+                stats.count(Status::Running),
+                stats.count(Status::Succeeded),
+                stats.count(Status::Failed),
+                width = width
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the character of a name as it is written in a folded stack.
+fn folded_char(c: char) -> char {
+    if c == ';' || c.is_whitespace() {
+        '_'
+    } else {
+        c
+    }
+}
+
+/// Adds the entries of the node and its descendants in depth-first order.
+fn collect<W>(node: &Node<W>, entries: &mut Vec<ProfileEntry>) {
+    if let (Some(stats), Some(id), Some(path)) = (node.stats(), node.id(), node.path()) {
+        let children: Duration = node
+            .children()
+            .iter()
+            .filter_map(|c| c.stats())
+            .map(|s| s.total())
+            .sum();
+        entries.push(ProfileEntry {
+            id,
+            path: path.to_owned(),
+            stats: *stats,
+            self_time: stats.total().checked_sub(children).unwrap_or_default(),
+        });
+    }
+
+    for child in node.children() {
+        collect(child, entries);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bt::BehaviorTree;
+    use crate::clock::MockClock;
+    use crate::std_nodes::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn stats() {
+        let clock = Arc::new(MockClock::new());
+        let root = Sequence::new(vec![
+            InlineAction::new({
+                let clock = clock.clone();
+                move |_| {
+                    clock.advance(Duration::from_millis(2));
+                    Status::Succeeded
+                }
+            })
+            .named(Some("slow")),
+            AlwaysFail::new(),
+        ]);
+        let mut tree = BehaviorTree::new(root.named(Some("root")));
+        tree.set_clock(clock);
+
+        tree.tick(&mut ());
+        assert!(tree.root().stats().is_none());
+
+        tree.set_profiling(true);
+        tree.tick(&mut ());
+        tree.tick(&mut ());
+
+        let slow = tree.find("root/slow").unwrap().stats().unwrap();
+        assert_eq!(slow.ticks(), 2);
+        assert_eq!(slow.count(Status::Succeeded), 2);
+        assert_eq!(slow.min(), Duration::from_millis(2));
+        assert_eq!(slow.max(), Duration::from_millis(2));
+        assert_eq!(slow.total(), Duration::from_millis(4));
+
+        let profile = tree.profile();
+        let paths: Vec<_> = profile.entries().iter().map(|e| &e.path[..]).collect();
+        assert_eq!(paths, vec!["root", "root/slow", "root/AlwaysFail"]);
+        assert_eq!(profile.entries()[0].stats.count(Status::Failed), 2);
+        assert_eq!(profile.entries()[0].self_time, Duration::default());
+
+        assert_eq!(
+            profile.to_folded(),
+            "root 0\nroot;slow 4000\nroot;AlwaysFail 0\n"
+        );
+        assert_eq!(profile.to_string().lines().count(), 4);
+
+        tree.clear_profile();
+        assert!(tree.profile().entries().is_empty());
+    }
+
+    #[test]
+    fn folded_names() {
+        let root = Sequence::new(vec![
+            AlwaysSucceed::new().named(Some("a;b c")),
+            AlwaysSucceed::new().named(Some("d/e")),
+        ]);
+        let mut tree = BehaviorTree::new(root.named(Some("root")));
+        tree.set_clock(Arc::new(MockClock::new()));
+        tree.set_profiling(true);
+        tree.tick(&mut ());
+
+        assert_eq!(
+            tree.profile().to_folded(),
+            "root 0\nroot;a_b_c 0\nroot;d/e 0\n"
+        );
+    }
+}
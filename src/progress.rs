@@ -0,0 +1,83 @@
+//! Progress reported by nodes while they are running.
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+/// How far a running node has come.
+///
+/// Every part of the progress is optional. Nodes report progress through
+/// `Tickable::take_progress`, after which it can be read with `Node::progress`
+/// and is passed to `TreeObserver::on_progress`.
+///
+/// # Examples
+///
+/// ```
+/// # use aspen::progress::Progress;
+/// let progress = Progress::percent(42.0).with_message("navigating");
+/// assert_eq!(progress.to_string(), "navigating: 42%");
+/// ```
+#[derive(Clone, Default)]
+pub struct Progress {
+    /// The percentage of the work that is done, from 0 to 100.
+    pub percent: Option<f64>,
+
+    /// A description of what the node is doing.
+    pub message: Option<String>,
+
+    /// Arbitrary data for consumers that know what to expect.
+    pub payload: Option<Arc<dyn Any + Send + Sync>>,
+}
+impl Progress {
+    /// Creates progress with only a percentage.
+    pub fn percent(percent: f64) -> Progress {
+        Progress {
+            percent: Some(percent),
+            ..Progress::default()
+        }
+    }
+
+    /// Creates progress with only a message.
+    pub fn message<S: Into<String>>(message: S) -> Progress {
+        Progress {
+            message: Some(message.into()),
+            ..Progress::default()
+        }
+    }
+
+    /// Sets the message.
+    pub fn with_message<S: Into<String>>(mut self, message: S) -> Progress {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Sets the payload.
+    pub fn with_payload<T: Any + Send + Sync>(mut self, payload: T) -> Progress {
+        self.payload = Some(Arc::new(payload));
+        self
+    }
+
+    /// Returns the payload if it is of the given type.
+    pub fn payload<T: Any>(&self) -> Option<&T> {
+        self.payload.as_ref().and_then(|p| p.downcast_ref())
+    }
+}
+impl fmt::Display for Progress {
+    /// Writes the message and the percentage, whichever are present.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.message, self.percent) {
+            (Some(m), Some(p)) => write!(f, "{}: {}%", m, p),
+            (Some(m), None) => write!(f, "{}", m),
+            (None, Some(p)) => write!(f, "{}%", p),
+            (None, None) => Ok(()),
+        }
+    }
+}
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("percent", &self.percent)
+            .field("message", &self.message)
+            .field("payload", &self.payload.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
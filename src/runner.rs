@@ -0,0 +1,160 @@
+//! Configuration and statistics for running a tree at a fixed rate.
+//!
+//! `BehaviorTree::run_with` ticks a tree on a fixed schedule until it
+//! finishes. When a tick takes longer than the period, the `OverrunPolicy` of
+//! the `RunConfig` decides what happens to the schedule. While the tree runs,
+//! it keeps `RunStats` about how closely the schedule was followed, which the
+//! hook can read through `BehaviorTree::run_stats`.
+//!
+//! # Examples
+//!
+//! ```
+//! # use aspen::std_nodes::*;
+//! # use aspen::runner::{OverrunPolicy, RunConfig};
+//! # use aspen::{BehaviorTree, Status};
+//! let mut tree = BehaviorTree::new(Repeat::with_limit(5, AlwaysSucceed::new()));
+//! let config = RunConfig::new(200.0).overrun(OverrunPolicy::Skip);
+//!
+//! let status = tree.run_with(config, &mut (), Some(|t: &BehaviorTree<()>| {
+//!     let stats = t.run_stats();
+//!     println!("Worst jitter after {} ticks: {:?}", stats.ticks, stats.max_jitter);
+//! }));
+//!
+//! assert_eq!(status, Status::Succeeded);
+//! assert_eq!(tree.run_stats().ticks, 5);
+//! ```
+use std::time::Duration;
+
+/// What a running tree does when a tick overruns its period.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum OverrunPolicy {
+    /// Ticks again right away and starts the schedule over from then, so
+    /// that every later tick is delayed. This is the default, and what
+    /// `BehaviorTree::run` does.
+    #[default]
+    Reschedule,
+
+    /// Skips the ticks that were missed and waits for the next one on the
+    /// original schedule.
+    Skip,
+
+    /// Ticks again right away, without waiting, until the tree is back on the
+    /// original schedule.
+    CatchUp,
+
+    /// Stops the tree by resetting it and returns `Status::Failed`.
+    Fail,
+}
+
+/// How a tree should be run by `BehaviorTree::run_with`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RunConfig {
+    /// The number of ticks per second.
+    freq: f64,
+
+    /// What to do when a tick takes too long.
+    overrun: OverrunPolicy,
+}
+impl RunConfig {
+    /// Creates a configuration that ticks the tree at the given frequency in
+    /// Hertz.
+    ///
+    /// A frequency of zero ticks the tree once. An infinite frequency ticks
+    /// it as fast as possible, which never overruns, and so does a negative
+    /// or NaN frequency.
+    pub fn new(freq: f64) -> RunConfig {
+        RunConfig {
+            freq,
+            overrun: OverrunPolicy::default(),
+        }
+    }
+
+    /// Sets what to do when a tick takes longer than the period.
+    pub fn overrun(mut self, policy: OverrunPolicy) -> RunConfig {
+        self.overrun = policy;
+        self
+    }
+
+    /// Returns the frequency in Hertz.
+    pub fn freq(&self) -> f64 {
+        self.freq
+    }
+
+    /// Returns what to do when a tick takes longer than the period.
+    pub fn overrun_policy(&self) -> OverrunPolicy {
+        self.overrun
+    }
+
+    /// Returns the time between the starts of two ticks, or `None` if the
+    /// tree should only be ticked once.
+    ///
+    /// A frequency so low that the period does not fit in a `Duration` gets
+    /// the longest one instead.
+    pub(crate) fn period(&self) -> Option<Duration> {
+        if self.freq == 0.0 {
+            None
+        } else if self.freq.is_nan() || self.freq < 0.0 || self.freq.is_infinite() {
+            Some(Duration::default())
+        } else {
+            Some(Duration::try_from_secs_f64(self.freq.recip()).unwrap_or(Duration::MAX))
+        }
+    }
+}
+
+/// Statistics about how closely a running tree kept to its schedule.
+///
+/// The period is the time between the starts of two consecutive ticks, and
+/// the jitter is how far it was from the configured period, in either
+/// direction.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct RunStats {
+    /// The number of ticks so far.
+    pub ticks: u64,
+
+    /// The number of ticks that took longer than the period.
+    pub overruns: u64,
+
+    /// The number of ticks that were skipped by `OverrunPolicy::Skip`.
+    pub skipped: u64,
+
+    /// The longest time it took to tick the tree, including the hook.
+    pub max_tick_time: Duration,
+
+    /// The shortest period.
+    pub min_period: Duration,
+
+    /// The longest period.
+    pub max_period: Duration,
+
+    /// The average period.
+    pub mean_period: Duration,
+
+    /// The largest difference between a period and the configured period.
+    pub max_jitter: Duration,
+
+    /// The average difference between a period and the configured period.
+    pub mean_jitter: Duration,
+}
+impl RunStats {
+    /// Adds the time between the start of the last tick and the current one.
+    pub(crate) fn record_period(&mut self, period: Duration, target: Duration) {
+        // The first tick has no period, so this is the n-th period
+        let n = self.ticks.saturating_sub(1) as f64;
+        let jitter = period.abs_diff(target);
+
+        self.min_period = if n == 0.0 {
+            period
+        } else {
+            self.min_period.min(period)
+        };
+        self.max_period = self.max_period.max(period);
+        self.mean_period = mean(self.mean_period, period, n);
+        self.max_jitter = self.max_jitter.max(jitter);
+        self.mean_jitter = mean(self.mean_jitter, jitter, n);
+    }
+}
+
+/// Adds a value to the average of the `n` values before it.
+fn mean(mean: Duration, value: Duration, n: f64) -> Duration {
+    Duration::from_secs_f64((mean.as_secs_f64() * n + value.as_secs_f64()) / (n + 1.0))
+}
@@ -1,3 +1,7 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
 /// Represents the status of a given node in the behavior tree.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Status {
@@ -18,4 +22,42 @@ impl Status {
     pub fn is_done(&self) -> bool {
         *self == Status::Succeeded || *self == Status::Failed
     }
-}
\ No newline at end of file
+}
+impl FromStr for Status {
+    type Err = ParseStatusError;
+
+    /// Parses the name of a status, as written by `Debug`.
+    fn from_str(s: &str) -> Result<Status, ParseStatusError> {
+        match s {
+            "Running" => Ok(Status::Running),
+            "Succeeded" => Ok(Status::Succeeded),
+            "Failed" => Ok(Status::Failed),
+            _ => Err(ParseStatusError(s.to_owned())),
+        }
+    }
+}
+
+/// An error from parsing a string that is not the name of a `Status`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseStatusError(String);
+impl fmt::Display for ParseStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"{}\" is not a status", self.0)
+    }
+}
+impl Error for ParseStatusError {}
+
+#[cfg(test)]
+mod tests {
+    use super::Status;
+
+    #[test]
+    fn parse_debug_names() {
+        for &status in &[Status::Running, Status::Succeeded, Status::Failed] {
+            assert_eq!(format!("{:?}", status).parse(), Ok(status));
+        }
+
+        let err = "running".parse::<Status>().unwrap_err();
+        assert_eq!(err.to_string(), "\"running\" is not a status");
+    }
+}
@@ -1,10 +1,23 @@
 //! Nodes that cause the execution of tasks.
+use crate::blackboard::{HasBlackboard, Key};
+use crate::context::{noop_waker, Context};
+use crate::executor::{ActionExecutor, Job, PanicPolicy, SpawnExecutor};
+use crate::failure::FailureReason;
 use crate::node::{Node, Tickable};
+use crate::progress::Progress;
 use crate::status::Status;
+use std::any::Any;
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::rc::Rc;
 use std::sync::mpsc;
-use std::sync::mpsc::TryRecvError;
-use std::sync::Arc;
-use std::thread;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{RecvTimeoutError, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::task::{self, Poll};
+use std::time::Duration;
 
 /// A node that manages the execution of tasks in a separate thread.
 ///
@@ -19,6 +32,19 @@ use std::thread;
 /// Note that the supplied function will be called again the next tick if the
 /// function returns either `Initialized` or `Running`.
 ///
+/// Inside a `BehaviorTree`, the task is run on the tree's executor (see
+/// `BehaviorTree::set_executor`). If the executor is too busy to accept the
+/// task, the node stays running and tries again on the next tick.
+///
+/// Halting the node while the task is running does not block. The task keeps
+/// running in its thread and its result is discarded, so long tasks should be
+/// created with `Action::cancellable` and stop when they are told to.
+///
+/// A panic in the task is caught on the worker thread and handled according
+/// to the tree's `PanicPolicy` (see `BehaviorTree::set_panic_policy`), which
+/// makes the node fail unless configured otherwise. Observers are told about
+/// the panic through `TreeObserver::on_panic`.
+///
 /// # State
 ///
 /// **Initialized:** Before being ticked after either being created or reset,
@@ -29,7 +55,7 @@ use std::thread;
 ///
 /// **Succeeded:** When the function returns `Succeeded`.
 ///
-/// **Failed:** When the function returns `Failed`.
+/// **Failed:** When the function returns `Failed`, or when it panics.
 ///
 /// # Children
 ///
@@ -66,10 +92,29 @@ where
     W: Clone + Send + Sync + 'static,
 {
     /// The task which is to be run.
-    func: Arc<Fn(W) -> Status + Send + Sync>,
+    func: Arc<dyn Fn(W, ProgressReporter) -> Result<Status, FailureReason> + Send + Sync>,
+
+    /// Channel on which the task will communicate its result, or the
+    /// message of its panic.
+    rx: Option<mpsc::Receiver<Result<Result<Status, FailureReason>, String>>>,
+
+    /// The token that cancels the task that is currently running.
+    cancel: CancelToken,
+
+    /// The latest progress of the task that is currently running.
+    progress: Arc<Mutex<Option<Progress>>>,
+
+    /// The message of a panic that has not been reported yet.
+    panic: Option<String>,
+
+    /// Why the task failed on the last tick, if it gave a reason.
+    failure: Option<FailureReason>,
+
+    /// How long to wait for a cancelled task to finish when halted.
+    join_timeout: Option<Duration>,
 
-    /// Channel on which the task will communicate.
-    rx: Option<mpsc::Receiver<Status>>,
+    /// The context of the tree this node is part of, if any.
+    context: Option<Rc<Context>>,
 }
 impl<W> Action<W>
 where
@@ -79,29 +124,204 @@ where
     pub fn new<F>(task: F) -> Node<'static, W>
     where
         F: Fn(W) -> Status + Send + Sync + 'static,
+    {
+        Action::cancellable(move |world, _| task(world))
+    }
+
+    /// Creates a new Action node that will execute the given task, which
+    /// should stop early when the token it is given is cancelled.
+    ///
+    /// The token is cancelled when the node is halted or dropped while the
+    /// task is running. Halting does not wait for the task to stop.
+    ///
+    /// ```
+    /// # use aspen::std_nodes::*;
+    /// # use aspen::Status;
+    /// # use aspen::node::Tickable;
+    /// # use std::thread;
+    /// # use std::time::Duration;
+    /// let mut action = Action::cancellable(|_, cancel: CancelToken| {
+    ///     while !cancel.is_cancelled() {
+    ///         thread::sleep(Duration::from_millis(1));
+    ///     }
+    ///     Status::Failed
+    /// });
+    ///
+    /// assert_eq!(action.tick(&mut ()), Status::Running);
+    /// action.reset();
+    /// assert_eq!(action.status(), None);
+    /// ```
+    pub fn cancellable<F>(task: F) -> Node<'static, W>
+    where
+        F: Fn(W, CancelToken) -> Status + Send + Sync + 'static,
+    {
+        Action::build(None, move |world, r| {
+            Ok(task(world, r.cancel_token().clone()))
+        })
+    }
+
+    /// Creates a new Action node like `Action::cancellable`, except that
+    /// halting the node waits up to `timeout` for the task to stop.
+    ///
+    /// If the task does not stop in time, it is left to finish on its own.
+    pub fn cancellable_with_timeout<F>(timeout: Duration, task: F) -> Node<'static, W>
+    where
+        F: Fn(W, CancelToken) -> Status + Send + Sync + 'static,
+    {
+        Action::build(Some(timeout), move |world, r| {
+            Ok(task(world, r.cancel_token().clone()))
+        })
+    }
+
+    /// Creates a new Action node that will execute the given task, which can
+    /// report its progress through the reporter it is given.
+    ///
+    /// The latest progress is available from `Node::progress` while the node
+    /// is running and is passed to the tree's observers. The reporter also
+    /// tells the task when it has been cancelled, as with
+    /// `Action::cancellable`.
+    ///
+    /// ```
+    /// # use aspen::std_nodes::*;
+    /// # use aspen::Status;
+    /// # use aspen::node::Tickable;
+    /// # use aspen::progress::Progress;
+    /// # use std::sync::mpsc;
+    /// # use std::sync::Mutex;
+    /// # let (tx, rx) = mpsc::channel();
+    /// # let (done_tx, done_rx) = mpsc::channel();
+    /// # let (tx, done_rx) = (Mutex::new(tx), Mutex::new(done_rx));
+    /// let mut action = Action::with_progress(move |_, progress: ProgressReporter| {
+    ///     progress.report(Progress::percent(42.0).with_message("navigating"));
+    ///     # tx.lock().unwrap().send(()).unwrap();
+    ///     # done_rx.lock().unwrap().recv().unwrap();
+    ///     Status::Succeeded
+    /// });
+    ///
+    /// assert_eq!(action.tick(&mut ()), Status::Running);
+    /// # rx.recv().unwrap();
+    /// assert_eq!(action.tick(&mut ()), Status::Running);
+    /// assert_eq!(action.progress().unwrap().to_string(), "navigating: 42%");
+    /// # done_tx.send(()).unwrap();
+    /// ```
+    pub fn with_progress<F>(task: F) -> Node<'static, W>
+    where
+        F: Fn(W, ProgressReporter) -> Status + Send + Sync + 'static,
+    {
+        Action::build(None, move |world, r| Ok(task(world, r)))
+    }
+
+    /// Creates a new Action node that will execute the given task, which
+    /// fails with a reason by returning an error.
+    ///
+    /// The error is turned into the `FailureReason` of the node with its
+    /// `Display` implementation.
+    ///
+    /// ```
+    /// # use aspen::std_nodes::*;
+    /// # use aspen::Status;
+    /// # use aspen::node::Tickable;
+    /// let mut action = Action::fallible(|_| "x".parse::<u32>().map(|_| Status::Succeeded));
+    ///
+    /// while action.tick(&mut ()) == Status::Running {}
+    /// assert_eq!(action.status(), Some(Status::Failed));
+    /// assert_eq!(
+    ///     action.failure_reason().unwrap().message(),
+    ///     "invalid digit found in string"
+    /// );
+    /// ```
+    pub fn fallible<F, E>(task: F) -> Node<'static, W>
+    where
+        F: Fn(W) -> Result<Status, E> + Send + Sync + 'static,
+        E: fmt::Display,
+    {
+        Action::build(None, move |world, _| {
+            task(world).map_err(|e| FailureReason::new(e.to_string()))
+        })
+    }
+
+    /// Creates the node for any of the constructors.
+    fn build<F>(join_timeout: Option<Duration>, task: F) -> Node<'static, W>
+    where
+        F: Fn(W, ProgressReporter) -> Result<Status, FailureReason> + Send + Sync + 'static,
     {
         let internals = Action {
             func: Arc::new(task),
             rx: None,
+            cancel: CancelToken::new(),
+            progress: Arc::new(Mutex::new(None)),
+            panic: None,
+            failure: None,
+            join_timeout,
+            context: None,
         };
 
         Node::new(internals)
     }
 
-    /// Launches a new worker thread to run the task.
+    /// Submits the task to the tree's executor, or to a new thread if the
+    /// node is not part of a tree.
+    ///
+    /// If the executor refuses the task, the node is left as if it was never
+    /// started so that the next tick tries again.
     fn start_thread(&mut self, world: &W) {
-        // Create our new channels
-        let (tx, rx) = mpsc::sync_channel(0);
+        // Create our new channels. The status is buffered so that workers
+        // do not have to wait for the next tick before taking another task
+        let (tx, rx) = mpsc::sync_channel(1);
 
         // Then clone the function so we can move it
         let func_clone = self.func.clone();
 
-        // Finally, boot up the thread
+        // Every run gets its own token and progress so that an abandoned run
+        // does not affect later ones
+        self.cancel = CancelToken::new();
+        self.progress = Arc::new(Mutex::new(None));
+        let reporter = ProgressReporter {
+            cancel: self.cancel.clone(),
+            latest: self.progress.clone(),
+        };
+
+        // Finally, hand the task off. Nobody is listening if the node was
+        // halted, so the result is discarded
         let world_clone = world.clone();
-        thread::spawn(move || tx.send((func_clone)(world_clone)).unwrap());
+        let job: Job = Box::new(move || {
+            let task = AssertUnwindSafe(|| (func_clone)(world_clone, reporter));
+            let result = panic::catch_unwind(task);
+            let _ = tx.send(result.map_err(|p| panic_message(&*p)));
+        });
+        let accepted = match self.context {
+            Some(ref context) => context.executor().try_execute(job).is_ok(),
+            None => SpawnExecutor.try_execute(job).is_ok(),
+        };
 
         // Store the rx for later use
-        self.rx = Some(rx);
+        if accepted {
+            self.rx = Some(rx);
+        } else {
+            debug!("Executor is busy, action will be started on a later tick");
+        }
+    }
+
+    /// Decides what happens to the node now that its task has panicked.
+    fn panicked(&mut self, message: String) -> Status {
+        warn!("Action panicked: {}", message);
+        let policy = match self.context {
+            Some(ref context) => context.panic_policy(),
+            None => PanicPolicy::default(),
+        };
+
+        match policy {
+            PanicPolicy::Fail => {
+                self.failure = Some(FailureReason::new(format!("Task panicked: {}", message)));
+                self.panic = Some(message);
+                Status::Failed
+            }
+            PanicPolicy::Restart => {
+                self.panic = Some(message);
+                Status::Running
+            }
+            PanicPolicy::Propagate => panic!("Action panicked: {}", message),
+        }
     }
 }
 impl<W> Tickable<W> for Action<W>
@@ -114,46 +334,175 @@ where
     /// clone `world` and use the clone as the argument for the task function,
     /// which will be run in a separate thread. Usually, this should be an `Arc`.
     fn tick(&mut self, world: &mut W) -> Status {
-        let (status, reset) = if let Some(ref mut rx) = self.rx {
+        let (result, reset) = if let Some(ref mut rx) = self.rx {
+            // The task is finished once it has sent its status, so the next
+            // tick starts it again. This lets decorators like `Repeat`, which
+            // do not reset their child, run the task more than once.
             match rx.try_recv() {
-                Ok(Status::Running) => (Status::Running, true),
-                Ok(s) => (s, false),
-                Err(TryRecvError::Empty) => (Status::Running, false),
-                Err(e) => panic!("Thread died before finishing {}", e),
+                Ok(result) => (result, true),
+                Err(TryRecvError::Empty) => (Ok(Ok(Status::Running)), false),
+                Err(TryRecvError::Disconnected) => {
+                    // The executor dropped the task without running it
+                    (Err("Task was dropped before finishing".to_owned()), true)
+                }
             }
         } else {
             self.start_thread(world);
-            (Status::Running, false)
+            (Ok(Ok(Status::Running)), false)
         };
 
         if reset {
             self.rx.take();
         }
 
-        status
+        match result {
+            Ok(Ok(status)) => status,
+            Ok(Err(reason)) => {
+                self.failure = Some(reason);
+                Status::Failed
+            }
+            Err(message) => self.panicked(message),
+        }
     }
 
     /// Resets the internal state of this node.
     ///
-    /// If there is a task currently running, this will block until the task is
-    /// completed.
+    /// The node is only reset once the task has finished, so this forgets
+    /// the finished task.
     fn reset(&mut self) {
-        // I debated what to do here for a while. I could see someone wanting to detach
-        // the thread due to time constraints, but it seems to me that it would be better
-        // to avoid potential bugs that come from a node only looking like its been
-        // fully reset.
-        if let Some(ref mut rx) = self.rx {
-            rx.recv().unwrap();
-        }
         self.rx = None;
     }
 
+    /// Halts this node while the task is running.
+    ///
+    /// This cancels the task's token and, unless the node was created with a
+    /// join timeout, returns immediately. The worker thread is left to finish
+    /// on its own and its result is discarded.
+    fn halt(&mut self) {
+        self.cancel.cancel();
+        if let (Some(rx), Some(timeout)) = (self.rx.take(), self.join_timeout) {
+            if let Err(RecvTimeoutError::Timeout) = rx.recv_timeout(timeout) {
+                warn!("Halted action did not stop within {:?}", timeout);
+            }
+        }
+    }
+
+    /// Returns the progress the task reported since the last tick, if any.
+    fn take_progress(&mut self) -> Option<Progress> {
+        self.progress.lock().unwrap().take()
+    }
+
+    /// Returns the message of the task's last panic, if it has not been
+    /// returned before.
+    fn take_panic(&mut self) -> Option<String> {
+        self.panic.take()
+    }
+
+    /// Returns the reason the task failed with, if any.
+    fn take_failure(&mut self) -> Option<FailureReason> {
+        self.failure.take()
+    }
+
+    /// Keeps the context so that tasks can be run on the tree's executor.
+    fn attach(&mut self, context: &Rc<Context>) {
+        self.context = Some(context.clone());
+    }
+
     /// Returns the constant string "Action"
     fn type_name(&self) -> &'static str {
         "Action"
     }
 }
 
+impl<W> Drop for Action<W>
+where
+    W: Clone + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        // Let a task that is still running know that nobody is waiting for it
+        if self.rx.is_some() {
+            self.cancel.cancel();
+        }
+    }
+}
+
+/// Returns the message of a panic from its payload.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_owned()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Box<dyn Any>".to_owned()
+    }
+}
+
+/// A flag that tells the task of an `Action` that it should stop.
+///
+/// Tasks are expected to check the token periodically and return as soon as
+/// they reasonably can once it is cancelled. The status they return is
+/// ignored.
+#[derive(Clone, Default, Debug)]
+pub struct CancelToken {
+    /// Whether the token has been cancelled.
+    cancelled: Arc<AtomicBool>,
+}
+impl CancelToken {
+    /// Creates a new token that has not been cancelled.
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Cancels the token and every clone of it.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// A handle through which the task of an `Action` reports its progress.
+///
+/// Only the latest progress is kept, so tasks can report as often as they
+/// like. See `Action::with_progress`.
+#[derive(Clone)]
+pub struct ProgressReporter {
+    /// The token of the run this reporter belongs to.
+    cancel: CancelToken,
+
+    /// The latest progress that has not been seen by the node.
+    latest: Arc<Mutex<Option<Progress>>>,
+}
+impl ProgressReporter {
+    /// Reports the progress of the task.
+    pub fn report(&self, progress: Progress) {
+        *self.latest.lock().unwrap() = Some(progress);
+    }
+
+    /// Reports the percentage of the task that is done.
+    pub fn percent(&self, percent: f64) {
+        self.report(Progress::percent(percent));
+    }
+
+    /// Reports what the task is doing.
+    pub fn message<S: Into<String>>(&self, message: S) {
+        self.report(Progress::message(message));
+    }
+
+    /// Returns whether the task has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Returns the token that tells the task it has been cancelled.
+    pub fn cancel_token(&self) -> &CancelToken {
+        &self.cancel
+    }
+}
+
 /// Convenience macro for creating Action nodes.
 ///
 /// # Examples
@@ -172,6 +521,9 @@ macro_rules! Action {
     };
 }
 
+/// The task of an `InlineAction`.
+type InlineTask<'a, W> = dyn FnMut(&mut W) -> Result<Status, FailureReason> + 'a;
+
 /// A node that manages the execution of tasks within the ticking thread.
 ///
 /// This node is an alternative to a normal Action node which can be used when
@@ -219,33 +571,160 @@ macro_rules! Action {
 /// ```
 pub struct InlineAction<'a, W> {
     /// The task which is to be run.
-    func: Box<FnMut(&mut W) -> Status + 'a>,
+    func: Box<InlineTask<'a, W>>,
+
+    /// Why the task failed on the last tick, if it gave a reason.
+    failure: Option<FailureReason>,
 }
 impl<'a, W> InlineAction<'a, W>
 where
     W: 'a,
 {
     /// Creates a new `ShortAction` node that will execute the given task.
-    pub fn new<F>(task: F) -> Node<'a, W>
+    pub fn new<F>(mut task: F) -> Node<'a, W>
     where
         F: FnMut(&mut W) -> Status + 'a,
+    {
+        InlineAction::build(move |world: &mut W| Ok(task(world)))
+    }
+
+    /// Creates a new `InlineAction` node that will execute the given task,
+    /// which fails with a reason by returning an error.
+    ///
+    /// The error is turned into the `FailureReason` of the node with its
+    /// `Display` implementation.
+    ///
+    /// ```
+    /// # use aspen::std_nodes::*;
+    /// # use aspen::Status;
+    /// # use aspen::node::Tickable;
+    /// let mut action = InlineAction::fallible(|fuel: &mut u32| {
+    ///     if *fuel == 0 {
+    ///         return Err("Out of fuel");
+    ///     }
+    ///     *fuel -= 1;
+    ///     Ok(Status::Succeeded)
+    /// });
+    ///
+    /// assert_eq!(action.tick(&mut 0), Status::Failed);
+    /// assert_eq!(action.failure_reason().unwrap().message(), "Out of fuel");
+    /// ```
+    pub fn fallible<F, E>(mut task: F) -> Node<'a, W>
+    where
+        F: FnMut(&mut W) -> Result<Status, E> + 'a,
+        E: fmt::Display,
+    {
+        InlineAction::build(move |world: &mut W| {
+            task(world).map_err(|e| FailureReason::new(e.to_string()))
+        })
+    }
+
+    /// Creates the node for any of the constructors.
+    fn build<F>(task: F) -> Node<'a, W>
+    where
+        F: FnMut(&mut W) -> Result<Status, FailureReason> + 'a,
     {
         let internals = InlineAction {
             func: Box::new(task),
+            failure: None,
         };
 
         Node::new(internals)
     }
+
+    /// Creates a new `InlineAction` node that runs the task on the value
+    /// stored under `key` in the blackboard.
+    ///
+    /// The node fails without running the task if there is no such value.
+    ///
+    /// ```
+    /// # use aspen::std_nodes::*;
+    /// # use aspen::Status;
+    /// # use aspen::node::Tickable;
+    /// # use aspen::blackboard::{Blackboard, Key};
+    /// let count = Key::<u32>::new("count");
+    /// let mut board = Blackboard::new();
+    /// board.set(&count, 1);
+    ///
+    /// let mut action = InlineAction::with_key(count.clone(), |c| {
+    ///     *c += 1;
+    ///     Status::Succeeded
+    /// });
+    /// assert_eq!(action.tick(&mut board), Status::Succeeded);
+    /// assert_eq!(board.get(&count), Some(&2));
+    /// ```
+    pub fn with_key<T, F>(key: Key<T>, mut task: F) -> Node<'a, W>
+    where
+        W: HasBlackboard,
+        T: Any,
+        F: FnMut(&mut T) -> Status + 'a,
+    {
+        InlineAction::new(move |world: &mut W| {
+            world
+                .blackboard_mut()
+                .get_mut(&key)
+                .map_or(Status::Failed, &mut task)
+        })
+    }
+
+    /// Creates a new `InlineAction` node that reads `input`, runs the task on
+    /// it, and writes the result to `output`.
+    ///
+    /// The node succeeds if the task returns a value and fails if either the
+    /// input is missing or the task returns `None`.
+    ///
+    /// ```
+    /// # use aspen::std_nodes::*;
+    /// # use aspen::Status;
+    /// # use aspen::node::Tickable;
+    /// # use aspen::blackboard::{Blackboard, Key};
+    /// let a = Key::<u32>::new("a");
+    /// let b = Key::<u32>::new("b");
+    /// let mut board = Blackboard::new();
+    /// board.set(&a, 10);
+    ///
+    /// let mut action = InlineAction::map_key(a, b.clone(), |a| a.checked_sub(100));
+    /// assert_eq!(action.tick(&mut board), Status::Failed);
+    /// assert_eq!(board.get(&b), None);
+    /// ```
+    pub fn map_key<I, O, F>(input: Key<I>, output: Key<O>, mut task: F) -> Node<'a, W>
+    where
+        W: HasBlackboard,
+        I: Any,
+        O: Any + Send,
+        F: FnMut(&I) -> Option<O> + 'a,
+    {
+        InlineAction::new(move |world: &mut W| {
+            let board = world.blackboard_mut();
+            match board.get(&input).and_then(&mut task) {
+                Some(value) => {
+                    board.set(&output, value);
+                    Status::Succeeded
+                }
+                None => Status::Failed,
+            }
+        })
+    }
 }
 impl<'a, W> Tickable<W> for InlineAction<'a, W> {
     fn tick(&mut self, world: &mut W) -> Status {
-        (*self.func)(world)
+        match (*self.func)(world) {
+            Ok(status) => status,
+            Err(reason) => {
+                self.failure = Some(reason);
+                Status::Failed
+            }
+        }
     }
 
     fn reset(&mut self) {
         // No-op
     }
 
+    fn take_failure(&mut self) -> Option<FailureReason> {
+        self.failure.take()
+    }
+
     /// Returns the constant string "InlineAction"
     fn type_name(&self) -> &'static str {
         "InlineAction"
@@ -271,6 +750,158 @@ macro_rules! InlineAction {
     };
 }
 
+/// The future of an `AsyncAction`.
+type BoxFuture<'a> = Pin<Box<dyn Future<Output = Status> + 'a>>;
+
+/// A node that drives a future to completion, one poll per tick.
+///
+/// When ticked without a future in progress, the node calls the supplied
+/// function to create one. Every tick then polls the future a single time in
+/// the ticking thread, so no runtime or extra threads are needed. The output
+/// of the future is the status of the node.
+///
+/// The future is polled with the waker of the tree (see
+/// `BehaviorTree::set_waker`), or with a waker that does nothing if the tree
+/// has none or the node is not part of a tree. Halting or resetting the node
+/// drops the future, which cancels it.
+///
+/// The function is given the world, but the future cannot borrow from it.
+/// Anything the future needs should be cloned or moved into it.
+///
+/// # State
+///
+/// **Initialized:** Before being ticked after either being created or reset.
+///
+/// **Running:** While the future is pending, or if it resolved to `Running`.
+///
+/// **Succeeded:** When the future resolves to `Succeeded`.
+///
+/// **Failed:** When the future resolves to `Failed`.
+///
+/// # Children
+///
+/// None.
+///
+/// # Examples
+///
+/// ```
+/// # use aspen::std_nodes::*;
+/// # use aspen::Status;
+/// # use aspen::node::Tickable;
+/// # use std::future::Future;
+/// # use std::pin::Pin;
+/// # use std::task::{Context, Poll};
+/// /// A future that is pending the first time it is polled.
+/// struct YieldOnce(bool);
+/// impl Future for YieldOnce {
+///     type Output = ();
+///     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+///         if self.0 {
+///             return Poll::Ready(());
+///         }
+///         self.0 = true;
+///         cx.waker().wake_by_ref();
+///         Poll::Pending
+///     }
+/// }
+///
+/// let mut action = AsyncAction::new(|target: &mut u32| {
+///     let target = *target;
+///     async move {
+///         YieldOnce(false).await;
+///         if target > 10 { Status::Succeeded } else { Status::Failed }
+///     }
+/// });
+///
+/// assert_eq!(action.tick(&mut 42), Status::Running);
+/// assert_eq!(action.tick(&mut 42), Status::Succeeded);
+/// ```
+pub struct AsyncAction<'a, W> {
+    /// Creates the future for each run of the node.
+    func: Box<dyn FnMut(&mut W) -> BoxFuture<'a> + 'a>,
+
+    /// The future that is currently being driven, if any.
+    future: Option<BoxFuture<'a>>,
+
+    /// The context of the tree this node is part of, if any.
+    context: Option<Rc<Context>>,
+}
+impl<'a, W> AsyncAction<'a, W>
+where
+    W: 'a,
+{
+    /// Creates a new `AsyncAction` node that drives the futures returned by
+    /// the given function.
+    pub fn new<F, T>(mut task: F) -> Node<'a, W>
+    where
+        F: FnMut(&mut W) -> T + 'a,
+        T: Future<Output = Status> + 'a,
+    {
+        let internals = AsyncAction {
+            func: Box::new(move |world: &mut W| {
+                Box::pin(task(world)) as BoxFuture<'a>
+            }),
+            future: None,
+            context: None,
+        };
+
+        Node::new(internals)
+    }
+}
+impl<'a, W> Tickable<W> for AsyncAction<'a, W> {
+    /// Polls the future a single time, creating it first if needed.
+    fn tick(&mut self, world: &mut W) -> Status {
+        let func = &mut self.func;
+        let future = self.future.get_or_insert_with(|| func(world));
+
+        let waker = match self.context {
+            Some(ref context) => context.waker(),
+            None => noop_waker(),
+        };
+        match future.as_mut().poll(&mut task::Context::from_waker(&waker)) {
+            Poll::Ready(status) => {
+                // Like `Action`, a finished node starts over if it is ticked
+                self.future = None;
+                status
+            }
+            Poll::Pending => Status::Running,
+        }
+    }
+
+    /// Drops the future, if any.
+    fn reset(&mut self) {
+        self.future = None;
+    }
+
+    /// Keeps the context so that futures can be polled with the tree's waker.
+    fn attach(&mut self, context: &Rc<Context>) {
+        self.context = Some(context.clone());
+    }
+
+    /// Returns the constant string "AsyncAction"
+    fn type_name(&self) -> &'static str {
+        "AsyncAction"
+    }
+}
+
+/// Convenience macro for creating AsyncAction nodes.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate aspen;
+/// # use aspen::Status;
+/// # fn main() {
+/// let mut action = AsyncAction!{ |_: &mut ()| async { Status::Succeeded } };
+/// # }
+/// ```
+#[macro_export]
+macro_rules! AsyncAction {
+    ( $e:expr ) => {
+        $crate::std_nodes::AsyncAction::new($e)
+    };
+}
+
 #[cfg(test)]
 mod test {
     use crate::node::Tickable;
@@ -353,4 +984,298 @@ mod test {
             Status::Running
         );
     }
+
+    #[test]
+    fn halt_cancels() {
+        let (tx, rx) = mpsc::channel();
+        let mut action = Action::cancellable(move |_, cancel: CancelToken| {
+            while !cancel.is_cancelled() {
+                thread::sleep(time::Duration::from_millis(1));
+            }
+            tx.send(()).unwrap();
+            Status::Succeeded
+        });
+
+        assert_eq!(action.tick(&mut ()), Status::Running);
+        action.reset();
+        assert_eq!(action.status(), None);
+        rx.recv_timeout(time::Duration::from_secs(5)).unwrap();
+    }
+
+    #[test]
+    fn halt_waits_for_timeout() {
+        let (tx, rx) = mpsc::channel();
+        let mut action = Action::cancellable_with_timeout(
+            time::Duration::from_secs(5),
+            move |_, cancel: CancelToken| {
+                while !cancel.is_cancelled() {
+                    thread::sleep(time::Duration::from_millis(1));
+                }
+                tx.send(()).unwrap();
+                Status::Failed
+            },
+        );
+
+        assert_eq!(action.tick(&mut ()), Status::Running);
+        action.reset();
+
+        // The task already stopped, so there is nothing left to wait for
+        assert_eq!(rx.try_recv(), Ok(()));
+    }
+
+    #[test]
+    fn halt_does_not_block() {
+        let (tx, rx) = mpsc::sync_channel::<()>(0);
+        let mrx = Mutex::new(rx);
+        let mut action = Action::new(move |_| {
+            // Never receives anything until the sender is dropped
+            let _ = mrx.lock().unwrap().recv();
+            Status::Succeeded
+        });
+
+        assert_eq!(action.tick(&mut ()), Status::Running);
+        action.reset();
+        assert_eq!(action.status(), None);
+        drop(tx);
+    }
+
+    #[test]
+    fn executor_backpressure() {
+        use crate::executor::ThreadPool;
+        use crate::BehaviorTree;
+        use std::sync::Arc;
+
+        let (tx, rx) = mpsc::sync_channel(0);
+        let mrx = Arc::new(Mutex::new(rx));
+        let mrx2 = mrx.clone();
+        let root = Parallel::new(
+            2,
+            vec![
+                Action::new(move |_| mrx.lock().unwrap().recv().unwrap()),
+                Action::new(move |_| mrx2.lock().unwrap().recv().unwrap()),
+            ],
+        );
+        let pool = Arc::new(ThreadPool::new(1, 0));
+        let mut tree = BehaviorTree::new(root);
+        tree.set_executor(pool.clone());
+
+        // Only the first action fits in the pool
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        assert_eq!(pool.metrics().rejected, 1);
+
+        tx.send(Status::Succeeded).unwrap();
+        let mut sent = 1;
+        while tree.tick(&mut ()) == Status::Running {
+            if sent < 2 && pool.metrics().completed == 1 && tx.try_send(Status::Succeeded).is_ok() {
+                sent += 1;
+            }
+            thread::sleep(time::Duration::from_millis(1));
+        }
+        assert_eq!(tree.root().status(), Some(Status::Succeeded));
+    }
+
+    #[test]
+    fn progress() {
+        use crate::observer::{NodeInfo, TreeObserver};
+        use crate::progress::Progress;
+        use crate::BehaviorTree;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        struct Log(Rc<RefCell<Vec<String>>>);
+        impl TreeObserver for Log {
+            fn on_progress(&mut self, node: &NodeInfo, progress: &Progress) {
+                self.0
+                    .borrow_mut()
+                    .push(format!("{} {}", node.path(), progress));
+            }
+        }
+
+        let (step_tx, step_rx) = mpsc::channel::<()>();
+        let (done_tx, done_rx) = mpsc::channel();
+        let step_rx = Mutex::new(step_rx);
+        let done_tx = Mutex::new(done_tx);
+        let root = Action::with_progress(move |_, progress: ProgressReporter| {
+            for p in &[50.0, 100.0] {
+                step_rx.lock().unwrap().recv().unwrap();
+                progress.percent(*p);
+                done_tx.lock().unwrap().send(()).unwrap();
+            }
+            step_rx.lock().unwrap().recv().unwrap();
+            Status::Succeeded
+        });
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut tree = BehaviorTree::new(root.named(Some("move")));
+        tree.add_observer(Log(log.clone()));
+
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        assert!(tree.root().progress().is_none());
+
+        step_tx.send(()).unwrap();
+        done_rx.recv().unwrap();
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        assert_eq!(tree.root().progress().unwrap().percent, Some(50.0));
+
+        // Progress is kept until something new is reported
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        assert_eq!(tree.root().progress().unwrap().percent, Some(50.0));
+
+        step_tx.send(()).unwrap();
+        done_rx.recv().unwrap();
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        assert_eq!(tree.root().progress().unwrap().percent, Some(100.0));
+
+        step_tx.send(()).unwrap();
+        while tree.tick(&mut ()) == Status::Running {
+            thread::sleep(time::Duration::from_millis(1));
+        }
+        assert!(tree.root().progress().is_none());
+        assert_eq!(*log.borrow(), vec!["move 50%", "move 100%"]);
+    }
+
+    #[test]
+    fn panic_fails() {
+        use crate::observer::{NodeInfo, TreeObserver};
+        use crate::BehaviorTree;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        struct Log(Rc<RefCell<Vec<String>>>);
+        impl TreeObserver for Log {
+            fn on_panic(&mut self, node: &NodeInfo, message: &str) {
+                self.0
+                    .borrow_mut()
+                    .push(format!("{} {}", node.path(), message));
+            }
+        }
+
+        let root = Action::new(|_| panic!("Out of fuel"));
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut tree = BehaviorTree::new(root.named(Some("drive")));
+        tree.add_observer(Log(log.clone()));
+
+        let mut status = Status::Running;
+        while status == Status::Running {
+            status = tree.tick(&mut ());
+            thread::sleep(time::Duration::from_millis(1));
+        }
+
+        assert_eq!(status, Status::Failed);
+        assert_eq!(*log.borrow(), vec!["drive Out of fuel"]);
+        assert_eq!(
+            tree.root().failure_reason().unwrap().to_string(),
+            "drive: Task panicked: Out of fuel"
+        );
+    }
+
+    #[test]
+    fn panic_restarts() {
+        use crate::executor::PanicPolicy;
+        use crate::BehaviorTree;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let runs = Arc::new(AtomicUsize::new(0));
+        let runs_clone = runs.clone();
+        let root = Action::new(move |_| {
+            if runs_clone.fetch_add(1, Ordering::SeqCst) == 0 {
+                panic!("First run always fails");
+            }
+            Status::Succeeded
+        });
+        let mut tree = BehaviorTree::new(root);
+        tree.set_panic_policy(PanicPolicy::Restart);
+
+        while tree.tick(&mut ()) == Status::Running {
+            thread::sleep(time::Duration::from_millis(1));
+        }
+
+        assert_eq!(tree.root().status(), Some(Status::Succeeded));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Action panicked: Out of fuel")]
+    fn panic_propagates() {
+        use crate::executor::PanicPolicy;
+        use crate::BehaviorTree;
+
+        let mut tree = BehaviorTree::new(Action::new(|_| panic!("Out of fuel")));
+        tree.set_panic_policy(PanicPolicy::Propagate);
+
+        while tree.tick(&mut ()) == Status::Running {
+            thread::sleep(time::Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn async_drop_on_reset() {
+        use std::cell::Cell;
+        use std::future;
+        use std::rc::Rc;
+
+        /// Records that the future it is moved into was dropped.
+        struct DropFlag(Rc<Cell<u32>>);
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Rc::new(Cell::new(0));
+        let drops2 = drops.clone();
+        let mut action = AsyncAction::new(move |_: &mut ()| {
+            let flag = DropFlag(drops2.clone());
+            async move {
+                let _flag = flag;
+                future::pending::<Status>().await
+            }
+        });
+
+        assert_eq!(action.tick(&mut ()), Status::Running);
+        assert_eq!(action.tick(&mut ()), Status::Running);
+        assert_eq!(drops.get(), 0);
+        action.reset();
+        assert_eq!(drops.get(), 1);
+
+        // A new future is created on the next tick
+        assert_eq!(action.tick(&mut ()), Status::Running);
+        drop(action);
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn async_tree_waker() {
+        use crate::BehaviorTree;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::task::{Poll, Wake, Waker};
+
+        struct Counter(AtomicUsize);
+        impl Wake for Counter {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let counter = Arc::new(Counter(AtomicUsize::new(0)));
+        let mut polled = false;
+        let root = AsyncAction::new(move |_: &mut ()| {
+            let first = !polled;
+            polled = true;
+            std::future::poll_fn(move |cx| {
+                cx.waker().wake_by_ref();
+                if first {
+                    Poll::Pending
+                } else {
+                    Poll::Ready(Status::Failed)
+                }
+            })
+        });
+        let mut tree = BehaviorTree::new(root);
+        tree.set_waker(Waker::from(counter.clone()));
+
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    }
 }
@@ -1,6 +1,8 @@
 //! Nodes which query the state of the world.
+use crate::blackboard::{HasBlackboard, Key};
 use crate::node::{Node, Tickable};
 use crate::status::Status;
+use std::any::Any;
 
 /// A node whose status is determined by a function.
 ///
@@ -60,6 +62,33 @@ where
         };
         Node::new(internals)
     }
+
+    /// Constructs a new Condition node that runs the given function on the
+    /// value stored under `key` in the blackboard.
+    ///
+    /// The node fails if there is no such value.
+    ///
+    /// ```
+    /// # use aspen::std_nodes::*;
+    /// # use aspen::Status;
+    /// # use aspen::node::Tickable;
+    /// # use aspen::blackboard::{Blackboard, Key};
+    /// let battery = Key::<f32>::new("battery");
+    /// let mut board = Blackboard::new();
+    /// let mut node = Condition::with_key(battery.clone(), |&b| b > 0.2);
+    /// assert_eq!(node.tick(&mut board), Status::Failed);
+    ///
+    /// board.set(&battery, 0.8);
+    /// assert_eq!(node.tick(&mut board), Status::Succeeded);
+    /// ```
+    pub fn with_key<T, F>(key: Key<T>, func: F) -> Node<'a, W>
+    where
+        W: HasBlackboard,
+        T: Any,
+        F: Fn(&T) -> bool + 'a,
+    {
+        Condition::new(move |world: &W| world.blackboard().get(&key).is_some_and(&func))
+    }
 }
 impl<'a, W> Tickable<W> for Condition<'a, W> {
     fn tick(&mut self, world: &mut W) -> Status {
@@ -1,4 +1,6 @@
 //! Nodes that have a constant behavior.
+use crate::failure::FailureReason;
+use crate::format::Value;
 use crate::node::{Node, Tickable};
 use crate::status::Status;
 
@@ -53,9 +55,23 @@ use crate::status::Status;
 /// let mut node = AlwaysFail::with_child(AlwaysSucceed::new());
 /// assert_eq!(node.tick(&mut ()), Status::Failed);
 /// ```
+///
+/// The node can be given a reason to fail with:
+///
+/// ```
+/// # use aspen::std_nodes::*;
+/// # use aspen::Status;
+/// # use aspen::node::Tickable;
+/// let mut node = AlwaysFail::with_reason("Not implemented");
+/// assert_eq!(node.tick(&mut ()), Status::Failed);
+/// assert_eq!(node.failure_reason().unwrap().message(), "Not implemented");
+/// ```
 pub struct AlwaysFail<'a, W> {
     /// Optional child node.
     child: Option<Node<'a, W>>,
+
+    /// The reason this node fails with, if any.
+    reason: Option<FailureReason>,
 }
 impl<'a, W> AlwaysFail<'a, W>
 where
@@ -63,12 +79,25 @@ where
 {
     /// Construct a new AlwaysFail node.
     pub fn new() -> Node<'a, W> {
-        Node::new(AlwaysFail { child: None })
+        AlwaysFail::from_parts(None, None)
     }
 
     /// Construct a new AlwaysFail node that has a child.
     pub fn with_child(child: Node<'a, W>) -> Node<'a, W> {
-        Node::new(AlwaysFail { child: Some(child) })
+        AlwaysFail::from_parts(Some(child), None)
+    }
+
+    /// Construct a new AlwaysFail node that fails with the given reason.
+    pub fn with_reason<R: Into<FailureReason>>(reason: R) -> Node<'a, W> {
+        AlwaysFail::from_parts(None, Some(reason.into()))
+    }
+
+    /// Construct a new AlwaysFail node with an optional child and reason.
+    pub(crate) fn from_parts(
+        child: Option<Node<'a, W>>,
+        reason: Option<FailureReason>,
+    ) -> Node<'a, W> {
+        Node::new(AlwaysFail { child, reason })
     }
 }
 impl<'a, W> Tickable<W> for AlwaysFail<'a, W> {
@@ -88,6 +117,17 @@ impl<'a, W> Tickable<W> for AlwaysFail<'a, W> {
         }
     }
 
+    fn take_failure(&mut self) -> Option<FailureReason> {
+        self.reason.clone()
+    }
+
+    fn params(&self) -> Vec<(String, Value)> {
+        self.reason
+            .iter()
+            .map(|r| ("reason".to_owned(), Value::String(r.message().to_owned())))
+            .collect()
+    }
+
     fn children(&self) -> Vec<&Node<W>> {
         if let Some(ref child) = self.child {
             vec![child]
@@ -96,6 +136,10 @@ impl<'a, W> Tickable<W> for AlwaysFail<'a, W> {
         }
     }
 
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        self.child.iter_mut().for_each(f)
+    }
+
     /// Returns the string "AlwaysFail".
     fn type_name(&self) -> &'static str {
         "AlwaysFail"
@@ -225,6 +269,10 @@ impl<'a, W> Tickable<W> for AlwaysSucceed<'a, W> {
         }
     }
 
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        self.child.iter_mut().for_each(f)
+    }
+
     fn reset(&mut self) {
         if let Some(ref mut child) = self.child {
             child.reset();
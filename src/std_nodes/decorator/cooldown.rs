@@ -0,0 +1,293 @@
+use crate::context::{self, Context};
+use crate::failure::FailureReason;
+use crate::format::Value;
+use crate::node::{Node, Tickable};
+use crate::status::Status;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// A node that makes its child unavailable for a while after it finishes.
+///
+/// Once the child succeeds or fails, this node returns the same status and
+/// starts the cooldown. Until it is over, as measured with the clock of the
+/// tree, every tick fails right away with a reason, without ticking the
+/// child. After that the child is reset and ticked again.
+///
+/// The cooldown is kept when the node is reset, so that a parent starting
+/// over cannot skip it. A node created with `clear_on_reset` ends the
+/// cooldown instead.
+///
+/// # State
+///
+/// **Initialized:** Before being ticked after either being reset or created.
+///
+/// **Running:** While the child is running.
+///
+/// **Succeeded:** When the child succeeds.
+///
+/// **Failed:** When the child fails, or while cooling down.
+///
+/// # Children
+///
+/// One. It is ticked whenever the cooldown node is ticked and not cooling
+/// down, and reset whenever the cooldown node is reset or before it is ticked
+/// again after finishing.
+///
+/// # Examples
+///
+/// ```
+/// # use aspen::std_nodes::*;
+/// # use aspen::clock::MockClock;
+/// # use aspen::{BehaviorTree, Status};
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// let clock = Arc::new(MockClock::new());
+/// let grip = InlineAction::new(|_| Status::Succeeded);
+/// let root = Cooldown::new(Duration::from_secs(5), grip);
+/// let mut tree = BehaviorTree::new(root);
+/// tree.set_clock(clock.clone());
+///
+/// assert_eq!(tree.tick(&mut ()), Status::Succeeded);
+/// clock.advance(Duration::from_secs(4));
+/// assert_eq!(tree.tick(&mut ()), Status::Failed);
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(tree.tick(&mut ()), Status::Succeeded);
+/// ```
+pub struct Cooldown<'a, W> {
+    /// Child node.
+    child: Node<'a, W>,
+
+    /// How long the child is unavailable after it finishes.
+    cooldown: Duration,
+
+    /// Whether resetting this node ends the cooldown.
+    clear_on_reset: bool,
+
+    /// Whether the child has finished and is cooling down.
+    cooling: bool,
+
+    /// When the cooldown is over, if it is cooling down and the cooldown
+    /// does not go past the end of time.
+    ready_at: Option<Instant>,
+
+    /// Why this node failed on the last tick, if it was cooling down.
+    failure: Option<FailureReason>,
+
+    /// The context of the tree this node is part of, if any.
+    context: Option<Rc<Context>>,
+}
+impl<'a, W> Cooldown<'a, W>
+where
+    W: 'a,
+{
+    /// Creates a new Cooldown node that fails for `cooldown` after the child
+    /// finishes, even if it is reset in the meantime.
+    pub fn new(cooldown: Duration, child: Node<'a, W>) -> Node<'a, W> {
+        Cooldown::build(cooldown, false, child)
+    }
+
+    /// Creates a new Cooldown node that fails for `cooldown` after the child
+    /// finishes, or until it is reset.
+    pub fn clear_on_reset(cooldown: Duration, child: Node<'a, W>) -> Node<'a, W> {
+        Cooldown::build(cooldown, true, child)
+    }
+
+    /// Creates the node with the given reset behavior.
+    pub(crate) fn build(
+        cooldown: Duration,
+        clear_on_reset: bool,
+        child: Node<'a, W>,
+    ) -> Node<'a, W> {
+        let internals = Cooldown {
+            child,
+            cooldown,
+            clear_on_reset,
+            cooling: false,
+            ready_at: None,
+            failure: None,
+            context: None,
+        };
+        Node::new(internals)
+    }
+}
+impl<'a, W> Tickable<W> for Cooldown<'a, W> {
+    fn tick(&mut self, world: &mut W) -> Status {
+        let now = context::now(&self.context);
+        if self.cooling {
+            let message = match self.ready_at {
+                Some(ready_at) if now >= ready_at => None,
+                Some(ready_at) => Some(format!("Cooling down for another {:?}", ready_at - now)),
+                None => Some("Cooling down forever".to_owned()),
+            };
+            if let Some(message) = message {
+                self.failure = Some(FailureReason::new(message));
+                return Status::Failed;
+            }
+
+            self.cooling = false;
+            self.ready_at = None;
+        }
+
+        if self.child.status().is_some_and(|s| s.is_done()) {
+            self.child.reset();
+        }
+
+        let status = self.child.tick(world);
+        if status.is_done() {
+            self.cooling = true;
+            self.ready_at = now.checked_add(self.cooldown);
+        }
+
+        status
+    }
+
+    fn reset(&mut self) {
+        if self.clear_on_reset {
+            self.cooling = false;
+            self.ready_at = None;
+        }
+        self.failure = None;
+        self.child.reset();
+    }
+
+    fn children(&self) -> Vec<&Node<'_, W>> {
+        vec![&self.child]
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        f(&mut self.child)
+    }
+
+    fn attach(&mut self, context: &Rc<Context>) {
+        self.context = Some(context.clone());
+    }
+
+    fn take_failure(&mut self) -> Option<FailureReason> {
+        self.failure.take()
+    }
+
+    fn params(&self) -> Vec<(String, Value)> {
+        let mut params = vec![(
+            "cooldown".to_owned(),
+            Value::Number(self.cooldown.as_secs_f64()),
+        )];
+        if self.clear_on_reset {
+            params.push(("clear_on_reset".to_owned(), Value::Bool(true)));
+        }
+        params
+    }
+
+    /// Returns the string "Cooldown".
+    fn type_name(&self) -> &'static str {
+        "Cooldown"
+    }
+}
+
+/// Convenience macro for creating Cooldown nodes.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate aspen;
+/// # use std::time::Duration;
+/// # fn main() {
+/// let cooldown = Cooldown!{ Duration::from_secs(2),
+///     Condition!{ |&(a, b): &(u32, u32)| a < b }
+/// };
+/// # }
+/// ```
+#[macro_export]
+macro_rules! Cooldown {
+    ( $d:expr, $e:expr ) => {
+        $crate::std_nodes::Cooldown::new($d, $e)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cooldown;
+    use crate::bt::BehaviorTree;
+    use crate::clock::MockClock;
+    use crate::node::Tickable;
+    use crate::status::Status;
+    use crate::std_nodes::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn cooldown_after_finish() {
+        let clock = Arc::new(MockClock::new());
+        let child = CountedTick::new(Status::Failed, 2, true);
+        let mut tree = BehaviorTree::new(Cooldown::new(Duration::from_millis(100), child));
+        tree.set_clock(clock.clone());
+
+        assert_eq!(tree.tick(&mut ()), Status::Failed);
+        clock.advance(Duration::from_millis(40));
+        assert_eq!(tree.tick(&mut ()), Status::Failed);
+        assert_eq!(
+            tree.root().failure_reason().unwrap().message(),
+            "Cooling down for another 60ms"
+        );
+
+        // The tree resets the root between these ticks
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(tree.tick(&mut ()), Status::Failed);
+        assert!(tree.root().failure_reason().is_none());
+    }
+
+    #[test]
+    fn cooldown_not_while_running() {
+        let clock = Arc::new(MockClock::new());
+        let child = CountedTick::new(Status::Running, 3, true);
+        let mut tree = BehaviorTree::new(Cooldown::new(Duration::from_secs(1), child));
+        tree.set_clock(clock.clone());
+
+        for _ in 0..3 {
+            assert_eq!(tree.tick(&mut ()), Status::Running);
+        }
+    }
+
+    #[test]
+    fn cooldown_forever() {
+        let child = CountedTick::new(Status::Succeeded, 1, true);
+        let mut node = Cooldown::new(Duration::MAX, child);
+
+        assert_eq!(node.tick(&mut ()), Status::Succeeded);
+        for _ in 0..3 {
+            assert_eq!(node.tick(&mut ()), Status::Failed);
+        }
+        assert_eq!(
+            node.failure_reason().unwrap().message(),
+            "Cooling down forever"
+        );
+    }
+
+    #[test]
+    fn cooldown_clear_on_reset() {
+        let child = CountedTick::new(Status::Succeeded, 2, true);
+        let mut node = Cooldown::clear_on_reset(Duration::from_secs(60), child);
+
+        assert_eq!(node.tick(&mut ()), Status::Succeeded);
+        assert_eq!(node.tick(&mut ()), Status::Failed);
+        node.reset();
+        assert_eq!(node.tick(&mut ()), Status::Succeeded);
+    }
+
+    #[test]
+    fn reset_clears_failure() {
+        let mut internals = Cooldown {
+            child: AlwaysSucceed::new(),
+            cooldown: Duration::MAX,
+            clear_on_reset: false,
+            cooling: false,
+            ready_at: None,
+            failure: None,
+            context: None,
+        };
+
+        assert_eq!(internals.tick(&mut ()), Status::Succeeded);
+        assert_eq!(internals.tick(&mut ()), Status::Failed);
+        internals.reset();
+        assert!(internals.take_failure().is_none());
+    }
+}
@@ -1,5 +1,7 @@
+use crate::blackboard::{HasBlackboard, Key};
 use crate::node::{Node, Tickable};
 use crate::status::Status;
+use std::any::Any;
 
 /// A node whose status is determined by running a function on its child's status.
 ///
@@ -64,6 +66,34 @@ where
         };
         Node::new(internals)
     }
+
+    /// Creates a new Decorator node whose function is given the child's status
+    /// and the value stored under `key` in the blackboard, if there is one.
+    ///
+    /// ```
+    /// # use aspen::std_nodes::*;
+    /// # use aspen::Status;
+    /// # use aspen::node::Tickable;
+    /// # use aspen::blackboard::{Blackboard, Key};
+    /// let optional = Key::<bool>::new("optional");
+    /// let mut board = Blackboard::new();
+    /// board.set(&optional, true);
+    ///
+    /// let mut node = Decorator::with_key(AlwaysFail::new(), optional, |s, o| {
+    ///     if s == Status::Failed && o == Some(&true) { Status::Succeeded } else { s }
+    /// });
+    /// assert_eq!(node.tick(&mut board), Status::Succeeded);
+    /// ```
+    pub fn with_key<T, F>(child: Node<'a, W>, key: Key<T>, func: F) -> Node<'a, W>
+    where
+        W: HasBlackboard,
+        T: Any,
+        F: Fn(Status, Option<&T>) -> Status + 'a,
+    {
+        Decorator::new(child, move |status, world: &W| {
+            func(status, world.blackboard().get(&key))
+        })
+    }
 }
 impl<'a, W> Tickable<W> for Decorator<'a, W> {
     fn tick(&mut self, world: &mut W) -> Status {
@@ -81,6 +111,10 @@ impl<'a, W> Tickable<W> for Decorator<'a, W> {
         vec![&self.child]
     }
 
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        f(&mut self.child)
+    }
+
     /// Returns the string "Decorator".
     fn type_name(&self) -> &'static str {
         "Decorator"
@@ -150,6 +184,10 @@ impl<'a, W> Tickable<W> for Invert<'a, W> {
         vec![&self.child]
     }
 
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        f(&mut self.child)
+    }
+
     /// Returns the string "Invert".
     fn type_name(&self) -> &'static str {
         "Invert"
@@ -0,0 +1,161 @@
+use crate::context::{self, Context};
+use crate::format::Value;
+use crate::node::{Node, Tickable};
+use crate::status::Status;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// A node that waits before it starts ticking its child.
+///
+/// The delay starts on the first tick after the node is created or reset,
+/// and is measured with the clock of the tree. Until it is over, the node is
+/// running without ticking the child. After that, every tick is passed on to
+/// the child.
+///
+/// # State
+///
+/// **Initialized:** Before being ticked after either being reset or created.
+///
+/// **Running:** While waiting, or while the child is running.
+///
+/// **Succeeded:** When the child succeeds.
+///
+/// **Failed:** When the child fails.
+///
+/// # Children
+///
+/// One. It is ticked whenever the delay node is ticked after the delay, and
+/// reset whenever the delay node is reset.
+///
+/// # Examples
+///
+/// ```
+/// # use aspen::std_nodes::*;
+/// # use aspen::clock::MockClock;
+/// # use aspen::{BehaviorTree, Status};
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// let clock = Arc::new(MockClock::new());
+/// let root = Delay::new(Duration::from_secs(1), AlwaysSucceed::new());
+/// let mut tree = BehaviorTree::new(root);
+/// tree.set_clock(clock.clone());
+///
+/// assert_eq!(tree.tick(&mut ()), Status::Running);
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(tree.tick(&mut ()), Status::Succeeded);
+/// ```
+pub struct Delay<'a, W> {
+    /// Child node.
+    child: Node<'a, W>,
+
+    /// How long to wait before ticking the child.
+    delay: Duration,
+
+    /// When the delay started, if it has.
+    started: Option<Instant>,
+
+    /// The context of the tree this node is part of, if any.
+    context: Option<Rc<Context>>,
+}
+impl<'a, W> Delay<'a, W>
+where
+    W: 'a,
+{
+    /// Creates a new Delay node that starts ticking the child once `delay`
+    /// has passed.
+    pub fn new(delay: Duration, child: Node<'a, W>) -> Node<'a, W> {
+        let internals = Delay {
+            child,
+            delay,
+            started: None,
+            context: None,
+        };
+        Node::new(internals)
+    }
+}
+impl<'a, W> Tickable<W> for Delay<'a, W> {
+    fn tick(&mut self, world: &mut W) -> Status {
+        let now = context::now(&self.context);
+        let started = *self.started.get_or_insert(now);
+        if now.saturating_duration_since(started) < self.delay {
+            return Status::Running;
+        }
+
+        self.child.tick(world)
+    }
+
+    fn reset(&mut self) {
+        self.started = None;
+        self.child.reset();
+    }
+
+    fn children(&self) -> Vec<&Node<'_, W>> {
+        vec![&self.child]
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        f(&mut self.child)
+    }
+
+    fn attach(&mut self, context: &Rc<Context>) {
+        self.context = Some(context.clone());
+    }
+
+    fn params(&self) -> Vec<(String, Value)> {
+        vec![("delay".to_owned(), Value::Number(self.delay.as_secs_f64()))]
+    }
+
+    /// Returns the string "Delay".
+    fn type_name(&self) -> &'static str {
+        "Delay"
+    }
+}
+
+/// Convenience macro for creating Delay nodes.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate aspen;
+/// # use std::time::Duration;
+/// # fn main() {
+/// let delay = Delay!{ Duration::from_millis(500),
+///     Condition!{ |&(a, b): &(u32, u32)| a < b }
+/// };
+/// # }
+/// ```
+#[macro_export]
+macro_rules! Delay {
+    ( $d:expr, $e:expr ) => {
+        $crate::std_nodes::Delay::new($d, $e)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bt::BehaviorTree;
+    use crate::clock::MockClock;
+    use crate::node::Tickable;
+    use crate::status::Status;
+    use crate::std_nodes::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn delay_then_tick() {
+        let clock = Arc::new(MockClock::new());
+        let child = CountedTick::new(Status::Running, 2, true);
+        let mut tree = BehaviorTree::new(Delay::new(Duration::from_millis(100), child));
+        tree.set_clock(clock.clone());
+
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+
+        // Resetting starts the delay over
+        tree.reset();
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        assert_eq!(tree.root().children()[0].status(), None);
+    }
+}
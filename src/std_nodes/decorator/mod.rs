@@ -1,12 +1,31 @@
 //! Nodes that have a single child and modify the behavior of that child in some way.
 
+mod cooldown;
+pub use self::cooldown::Cooldown;
+
 mod decorator;
 pub use self::decorator::Decorator;
 pub use self::decorator::Invert;
 
+mod delay;
+pub use self::delay::Delay;
+
 mod repeat;
 pub use self::repeat::Repeat;
 
+mod retry;
+pub use self::retry::Backoff;
+pub use self::retry::Retry;
+
+mod status_map;
+pub use self::status_map::StatusMap;
+
+mod throttle;
+pub use self::throttle::Throttle;
+
+mod timeout;
+pub use self::timeout::Timeout;
+
 mod until;
 pub use self::until::UntilFail;
 pub use self::until::UntilSuccess;
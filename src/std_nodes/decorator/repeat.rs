@@ -1,3 +1,4 @@
+use crate::format::Value;
 use crate::node::{Node, Tickable};
 use crate::status::Status;
 
@@ -117,6 +118,17 @@ impl<'a, W> Tickable<W> for Repeat<'a, W> {
         vec![&self.child]
     }
 
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        f(&mut self.child)
+    }
+
+    fn params(&self) -> Vec<(String, Value)> {
+        self.attempt_limit
+            .map(|l| ("limit".to_owned(), Value::Number(l.into())))
+            .into_iter()
+            .collect()
+    }
+
     /// Returns the string "Repeat".
     fn type_name(&self) -> &'static str {
         "Repeat"
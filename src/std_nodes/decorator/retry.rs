@@ -0,0 +1,498 @@
+use crate::context::{self, Context};
+use crate::failure::FailureReason;
+use crate::format::Value;
+use crate::node::{Node, Tickable};
+use crate::status::Status;
+use std::collections::hash_map::RandomState;
+use std::convert::TryFrom;
+use std::hash::{BuildHasher, Hasher};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// How long a `Retry` node waits before each retry.
+///
+/// The first retry waits for the initial delay, and every retry after that
+/// waits `factor` times as long as the one before it, up to an optional
+/// maximum. Jitter spreads the delays out randomly by up to the given
+/// fraction in either direction, so that many nodes retrying at once do not
+/// all retry at the same time. The jitter is random unless a seed is given,
+/// in which case every node created with that backoff waits the same delays.
+///
+/// # Examples
+///
+/// ```
+/// # use aspen::std_nodes::Backoff;
+/// # use std::time::Duration;
+/// let backoff = Backoff::exponential(Duration::from_millis(100), 2.0)
+///     .with_max(Duration::from_secs(1));
+///
+/// assert_eq!(backoff.delay(0), Duration::from_millis(100));
+/// assert_eq!(backoff.delay(2), Duration::from_millis(400));
+/// assert_eq!(backoff.delay(5), Duration::from_secs(1));
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Backoff {
+    /// The delay before the first retry.
+    initial: Duration,
+
+    /// How much longer each delay is than the one before it.
+    factor: f64,
+
+    /// The longest delay, if any.
+    max: Option<Duration>,
+
+    /// The largest fraction by which a delay is randomly changed.
+    jitter: f64,
+
+    /// The seed of the generator for the jitter, if it is not random.
+    seed: Option<u64>,
+}
+impl Backoff {
+    /// Retries without waiting, on the tick after the failure.
+    pub fn none() -> Backoff {
+        Backoff::fixed(Duration::default())
+    }
+
+    /// Waits the same time before every retry.
+    pub fn fixed(delay: Duration) -> Backoff {
+        Backoff::exponential(delay, 1.0)
+    }
+
+    /// Waits `initial` before the first retry, and `factor` times longer
+    /// before every retry after that.
+    ///
+    /// # Panics
+    ///
+    /// If `factor` is not a finite number of at least one.
+    pub fn exponential(initial: Duration, factor: f64) -> Backoff {
+        assert!(
+            factor.is_finite() && factor >= 1.0,
+            "A backoff factor must be finite and at least 1, found {}",
+            factor
+        );
+
+        Backoff {
+            initial,
+            factor,
+            max: None,
+            jitter: 0.0,
+            seed: None,
+        }
+    }
+
+    /// Limits the delay to at most `max`.
+    pub fn with_max(mut self, max: Duration) -> Backoff {
+        self.max = Some(max);
+        self
+    }
+
+    /// Randomly lengthens or shortens every delay by up to the given
+    /// fraction of it, which is clamped between zero and one.
+    pub fn with_jitter(mut self, jitter: f64) -> Backoff {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Makes the jitter repeatable by starting its generator from `seed`.
+    pub fn with_seed(mut self, seed: u64) -> Backoff {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Returns the delay before the given retry, counting from zero, without
+    /// jitter.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let exponent = i32::try_from(retry).unwrap_or(i32::MAX);
+        let secs = self.initial.as_secs_f64() * self.factor.powi(exponent);
+        let max = self.max.map_or(f64::MAX, |m| m.as_secs_f64());
+        Duration::try_from_secs_f64(secs.min(max)).unwrap_or(Duration::MAX)
+    }
+
+    /// Returns the delay before the given retry with the jitter applied,
+    /// where `random` is between zero and one.
+    fn jittered(&self, retry: u32, random: f64) -> Duration {
+        let secs = self.delay(retry).as_secs_f64() * (1.0 + self.jitter * (2.0 * random - 1.0));
+        Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX)
+    }
+}
+impl Default for Backoff {
+    fn default() -> Backoff {
+        Backoff::none()
+    }
+}
+
+/// A node that retries its child when it fails.
+///
+/// When the child fails, it is reset and ticked again after waiting for the
+/// delay given by the backoff, measured with the clock of the tree. The node
+/// is running while it waits. Once the child has failed `retries` more times
+/// than the first, this node fails as well, with the failure reason of the
+/// child's last attempt.
+///
+/// # State
+///
+/// **Initialized:** Before being ticked after either being reset or created.
+///
+/// **Running:** While the child is running, or while waiting to retry it.
+///
+/// **Succeeded:** When the child succeeds.
+///
+/// **Failed:** When the child fails and there are no retries left.
+///
+/// # Children
+///
+/// One. It is ticked or reset whenever the retry node is ticked or reset,
+/// and also reset before every retry.
+///
+/// # Examples
+///
+/// ```
+/// # use aspen::std_nodes::*;
+/// # use aspen::clock::MockClock;
+/// # use aspen::{BehaviorTree, Status};
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// let clock = Arc::new(MockClock::new());
+/// let backoff = Backoff::fixed(Duration::from_secs(1));
+/// let root = Retry::with_backoff(1, backoff, AlwaysFail::with_reason("Door is locked"));
+/// let mut tree = BehaviorTree::new(root);
+/// tree.set_clock(clock.clone());
+///
+/// assert_eq!(tree.tick(&mut ()), Status::Running);
+/// assert_eq!(tree.tick(&mut ()), Status::Running);
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(tree.tick(&mut ()), Status::Failed);
+/// assert_eq!(tree.root().failure_reason().unwrap().message(), "Door is locked");
+/// ```
+pub struct Retry<'a, W> {
+    /// Child node.
+    child: Node<'a, W>,
+
+    /// The number of times the child is retried.
+    retries: u32,
+
+    /// How long to wait before each retry.
+    backoff: Backoff,
+
+    /// The number of retries so far.
+    attempts: u32,
+
+    /// Whether the child has failed and is waiting to be retried.
+    waiting: bool,
+
+    /// When to retry the child, if it is waiting and the delay does not go
+    /// past the end of time.
+    retry_at: Option<Instant>,
+
+    /// Why this node failed on the last tick, if the child gave no reason.
+    failure: Option<FailureReason>,
+
+    /// The state of the generator for the jitter.
+    rng: u64,
+
+    /// The context of the tree this node is part of, if any.
+    context: Option<Rc<Context>>,
+}
+impl<'a, W> Retry<'a, W>
+where
+    W: 'a,
+{
+    /// Creates a new Retry node that retries the child up to `retries` times
+    /// on the tick after it fails.
+    pub fn new(retries: u32, child: Node<'a, W>) -> Node<'a, W> {
+        Retry::with_backoff(retries, Backoff::none(), child)
+    }
+
+    /// Creates a new Retry node that retries the child up to `retries` times,
+    /// waiting according to the backoff before each retry.
+    pub fn with_backoff(retries: u32, backoff: Backoff, child: Node<'a, W>) -> Node<'a, W> {
+        let internals = Retry {
+            child,
+            retries,
+            backoff,
+            attempts: 0,
+            waiting: false,
+            retry_at: None,
+            failure: None,
+            rng: initial_state(
+                backoff
+                    .seed
+                    .unwrap_or_else(|| RandomState::new().build_hasher().finish()),
+            ),
+            context: None,
+        };
+        Node::new(internals)
+    }
+}
+impl<'a, W> Tickable<W> for Retry<'a, W> {
+    fn tick(&mut self, world: &mut W) -> Status {
+        let now = context::now(&self.context);
+        if self.waiting {
+            match self.retry_at {
+                Some(retry_at) if now >= retry_at => (),
+                _ => return Status::Running,
+            }
+
+            trace!("Retrying child, attempt {}", self.attempts + 1);
+            self.waiting = false;
+            self.retry_at = None;
+            self.child.reset();
+        }
+
+        let status = self.child.tick(world);
+        if status != Status::Failed {
+            return status;
+        }
+
+        if self.attempts >= self.retries {
+            if self.child.failure_reason().is_none() {
+                self.failure = Some(FailureReason::new(format!(
+                    "Failed {} times",
+                    self.attempts + 1
+                )));
+            }
+            return Status::Failed;
+        }
+
+        let random = next_random(&mut self.rng);
+        let delay = self.backoff.jittered(self.attempts, random);
+        self.attempts += 1;
+        self.waiting = true;
+        self.retry_at = now.checked_add(delay);
+        Status::Running
+    }
+
+    fn reset(&mut self) {
+        self.attempts = 0;
+        self.waiting = false;
+        self.retry_at = None;
+        self.failure = None;
+        self.child.reset();
+    }
+
+    fn children(&self) -> Vec<&Node<'_, W>> {
+        vec![&self.child]
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        f(&mut self.child)
+    }
+
+    fn attach(&mut self, context: &Rc<Context>) {
+        self.context = Some(context.clone());
+    }
+
+    fn take_failure(&mut self) -> Option<FailureReason> {
+        self.failure.take()
+    }
+
+    fn params(&self) -> Vec<(String, Value)> {
+        let backoff = &self.backoff;
+        let mut params = vec![("retries".to_owned(), Value::Number(self.retries.into()))];
+        if backoff.initial > Duration::default() {
+            params.push((
+                "delay".to_owned(),
+                Value::Number(backoff.initial.as_secs_f64()),
+            ));
+        }
+        if backoff.factor != 1.0 {
+            params.push(("factor".to_owned(), Value::Number(backoff.factor)));
+        }
+        if let Some(max) = backoff.max {
+            params.push(("max_delay".to_owned(), Value::Number(max.as_secs_f64())));
+        }
+        if backoff.jitter > 0.0 {
+            params.push(("jitter".to_owned(), Value::Number(backoff.jitter)));
+        }
+        if let Some(seed) = backoff.seed {
+            params.push(("seed".to_owned(), Value::Number(seed as f64)));
+        }
+        params
+    }
+
+    /// Returns the string "Retry".
+    fn type_name(&self) -> &'static str {
+        "Retry"
+    }
+}
+
+/// Returns the state of the generator for the jitter that starts from `seed`.
+fn initial_state(seed: u64) -> u64 {
+    // The generator never leaves a state of zero
+    if seed == 0 {
+        0x9e37_79b9_7f4a_7c15
+    } else {
+        seed
+    }
+}
+
+/// Returns a random number between zero and one, advancing the state of the
+/// xorshift64* generator.
+fn next_random(state: &mut u64) -> f64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    let bits = state.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
+
+/// Convenience macro for creating Retry nodes.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate aspen;
+/// # use aspen::std_nodes::Backoff;
+/// # use std::time::Duration;
+/// # fn main() {
+/// let retry = Retry!{ 3,
+///     Condition!{ |&(a, b): &(u32, u32)| a < b }
+/// };
+/// let backoff = Backoff::fixed(Duration::from_millis(250));
+/// let retry_later = Retry!{ 3, backoff,
+///     Condition!{ |&(a, b): &(u32, u32)| a < b }
+/// };
+/// # }
+/// ```
+#[macro_export]
+macro_rules! Retry {
+    ( $n:expr, $e:expr ) => {
+        $crate::std_nodes::Retry::new($n, $e)
+    };
+    ( $n:expr, $b:expr, $e:expr ) => {
+        $crate::std_nodes::Retry::with_backoff($n, $b, $e)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{initial_state, next_random, Backoff, Retry};
+    use crate::bt::BehaviorTree;
+    use crate::clock::MockClock;
+    use crate::node::Tickable;
+    use crate::status::Status;
+    use crate::std_nodes::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn retry_until_success() {
+        let mut failures = 2;
+        let child = InlineAction::new(move |_| {
+            if failures > 0 {
+                failures -= 1;
+                Status::Failed
+            } else {
+                Status::Succeeded
+            }
+        });
+        let mut node = Retry::new(2, child);
+
+        assert_eq!(node.tick(&mut ()), Status::Running);
+        assert_eq!(node.tick(&mut ()), Status::Running);
+        assert_eq!(node.tick(&mut ()), Status::Succeeded);
+    }
+
+    #[test]
+    fn retry_exponential() {
+        let clock = Arc::new(MockClock::new());
+        let backoff = Backoff::exponential(Duration::from_millis(100), 2.0);
+        let child = CountedTick::new(Status::Failed, 3, true);
+        let mut tree = BehaviorTree::new(Retry::with_backoff(2, backoff, child));
+        tree.set_clock(clock.clone());
+
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        clock.advance(Duration::from_millis(99));
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        clock.advance(Duration::from_millis(199));
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(tree.tick(&mut ()), Status::Failed);
+        assert_eq!(
+            tree.root().failure_reason().unwrap().message(),
+            "Failed 3 times"
+        );
+    }
+
+    #[test]
+    fn jitter_stays_in_range() {
+        let backoff = Backoff::fixed(Duration::from_millis(100)).with_jitter(0.5);
+        for &random in &[0.0, 0.25, 0.5, 1.0] {
+            let delay = backoff.jittered(0, random);
+            assert!(delay >= Duration::from_millis(50));
+            assert!(delay <= Duration::from_millis(150));
+        }
+        assert_eq!(backoff.jittered(0, 0.5), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn saturated_backoff() {
+        let backoff = Backoff::fixed(Duration::from_secs(u64::MAX)).with_jitter(0.5);
+        assert_eq!(backoff.jittered(0, 1.0), Duration::MAX);
+        assert!(backoff.jittered(0, 0.0) < Duration::MAX);
+
+        // A delay past the end of time never retries
+        let child = CountedTick::new(Status::Failed, 1, true);
+        let mut node = Retry::with_backoff(1, Backoff::fixed(Duration::MAX), child);
+        for _ in 0..3 {
+            assert_eq!(node.tick(&mut ()), Status::Running);
+        }
+    }
+
+    #[test]
+    fn huge_retry_count() {
+        let backoff =
+            Backoff::exponential(Duration::from_millis(100), 2.0).with_max(Duration::from_secs(1));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(1));
+        assert_eq!(
+            Backoff::fixed(Duration::from_millis(100)).delay(u32::MAX),
+            Duration::from_millis(100)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn shrinking_backoff() {
+        Backoff::exponential(Duration::from_secs(1), 0.5);
+    }
+
+    #[test]
+    fn seeded_jitter() {
+        let backoff = Backoff::fixed(Duration::from_millis(100))
+            .with_jitter(1.0)
+            .with_seed(7);
+        let delay = backoff.jittered(0, next_random(&mut initial_state(7)));
+
+        let clock = Arc::new(MockClock::new());
+        let child = CountedTick::new(Status::Failed, 2, true);
+        let mut tree = BehaviorTree::new(Retry::with_backoff(1, backoff, child));
+        tree.set_clock(clock.clone());
+
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        clock.advance(delay - Duration::from_nanos(1));
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        clock.advance(Duration::from_nanos(1));
+        assert_eq!(tree.tick(&mut ()), Status::Failed);
+    }
+
+    #[test]
+    fn reset_clears_failure() {
+        let mut internals = Retry {
+            child: AlwaysFail::new(),
+            retries: 0,
+            backoff: Backoff::none(),
+            attempts: 0,
+            waiting: false,
+            retry_at: None,
+            failure: None,
+            rng: initial_state(0),
+            context: None,
+        };
+
+        assert_eq!(internals.tick(&mut ()), Status::Failed);
+        internals.reset();
+        assert!(internals.take_failure().is_none());
+    }
+}
@@ -0,0 +1,277 @@
+use crate::failure::FailureReason;
+use crate::format::Value;
+use crate::node::{Node, Tickable};
+use crate::status::Status;
+
+/// A node that replaces the status of its child according to a table.
+///
+/// The table maps statuses of the child to the status this node returns.
+/// Statuses that are not in the table are returned unchanged. Unlike a
+/// `Decorator`, the table can be described and loaded with the `loader`
+/// module, and it can also turn a running child into a finished node.
+///
+/// Between two resets, mapping `Failed` to `Succeeded` returns the same
+/// statuses as `AlwaysSucceed::with_child`, and mapping `Succeeded` to
+/// `Failed` the same as `AlwaysFail::with_child`, tick for tick.
+///
+/// When a child that did not fail is mapped to `Failed`, this node gives the
+/// status of the child as the failure reason.
+///
+/// # State
+///
+/// **Initialized:** Before being ticked after either being created or reset.
+///
+/// **Running:** When the status of the child maps to `Running`.
+///
+/// **Succeeded:** When the status of the child maps to `Succeeded`.
+///
+/// **Failed:** When the status of the child maps to `Failed`.
+///
+/// # Children
+///
+/// One node that will be ticked or reset whenever the parent is ticked or
+/// reset. A running child is halted when its parent is reset, and a finished
+/// child is reset before it is ticked again, so that mapping a finished
+/// status to `Running` runs the child over.
+///
+/// # Examples
+///
+/// Fails instead of waiting for a child that is not done on the first tick:
+///
+/// ```
+/// # use aspen::std_nodes::*;
+/// # use aspen::Status;
+/// # use aspen::node::Tickable;
+/// let table = [(Status::Running, Status::Failed)];
+/// let mut node = StatusMap::new(&table, AlwaysRunning::new());
+/// assert_eq!(node.tick(&mut ()), Status::Failed);
+/// assert_eq!(node.failure_reason().unwrap().message(), "Child is Running");
+/// ```
+pub struct StatusMap<'a, W> {
+    /// Child node.
+    child: Node<'a, W>,
+
+    /// The status returned for a running, successful and failed child.
+    table: [Status; 3],
+
+    /// Why this node failed on the last tick, if the child did not fail.
+    failure: Option<FailureReason>,
+}
+impl<'a, W> StatusMap<'a, W>
+where
+    W: 'a,
+{
+    /// Creates a new StatusMap node with the given pairs of child status and
+    /// status to return instead.
+    ///
+    /// If a status of the child appears more than once, the last pair wins.
+    pub fn new(table: &[(Status, Status)], child: Node<'a, W>) -> Node<'a, W> {
+        let mut statuses = STATUSES;
+        for &(from, to) in table {
+            statuses[index(from)] = to;
+        }
+
+        let internals = StatusMap {
+            child,
+            table: statuses,
+            failure: None,
+        };
+        Node::new(internals)
+    }
+}
+impl<'a, W> Tickable<W> for StatusMap<'a, W> {
+    fn tick(&mut self, world: &mut W) -> Status {
+        if self.child.status().is_some_and(|s| s.is_done()) {
+            self.child.reset();
+        }
+
+        let child_status = self.child.tick(world);
+        let status = self.table[index(child_status)];
+        if status == Status::Failed && child_status != Status::Failed {
+            self.failure = Some(FailureReason::new(format!("Child is {:?}", child_status)));
+        }
+
+        status
+    }
+
+    fn reset(&mut self) {
+        self.failure = None;
+        self.child.reset();
+    }
+
+    fn children(&self) -> Vec<&Node<'_, W>> {
+        vec![&self.child]
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        f(&mut self.child)
+    }
+
+    fn take_failure(&mut self) -> Option<FailureReason> {
+        self.failure.take()
+    }
+
+    fn params(&self) -> Vec<(String, Value)> {
+        STATUSES
+            .iter()
+            .filter(|&&from| self.table[index(from)] != from)
+            .map(|&from| {
+                let to = self.table[index(from)];
+                (
+                    format!("{:?}", from).to_lowercase(),
+                    Value::String(format!("{:?}", to)),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the string "StatusMap".
+    fn type_name(&self) -> &'static str {
+        "StatusMap"
+    }
+}
+
+/// Every status, in the order of the table.
+const STATUSES: [Status; 3] = [Status::Running, Status::Succeeded, Status::Failed];
+
+/// Returns where the given status is kept in the table.
+fn index(status: Status) -> usize {
+    match status {
+        Status::Running => 0,
+        Status::Succeeded => 1,
+        Status::Failed => 2,
+    }
+}
+
+/// Convenience macro for creating StatusMap nodes.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate aspen;
+/// # use aspen::Status;
+/// # fn main() {
+/// let optional = StatusMap!{ &[(Status::Failed, Status::Succeeded)],
+///     Condition!{ |&a: &u32| a < 9 }
+/// };
+/// # }
+/// ```
+#[macro_export]
+macro_rules! StatusMap {
+    ( $t:expr, $e:expr ) => {
+        $crate::std_nodes::StatusMap::new($t, $e)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatusMap;
+    use crate::node::{Node, Tickable};
+    use crate::status::Status;
+    use crate::std_nodes::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn unmapped_statuses_pass_through() {
+        let table = [(Status::Failed, Status::Succeeded)];
+        let mut node = StatusMap::new(&table, YesTick::new(Status::Running));
+        assert_eq!(node.tick(&mut ()), Status::Running);
+
+        let mut node = StatusMap::new(&table, YesTick::new(Status::Failed));
+        assert_eq!(node.tick(&mut ()), Status::Succeeded);
+        assert!(node.failure_reason().is_none());
+    }
+
+    #[test]
+    fn running_to_failed_halts_on_reset() {
+        let table = [
+            (Status::Running, Status::Failed),
+            (Status::Succeeded, Status::Running),
+        ];
+        let mut node = StatusMap::new(&table, AlwaysRunning::new());
+        assert_eq!(node.tick(&mut ()), Status::Failed);
+        node.reset();
+        assert_eq!(node.children()[0].status(), None);
+
+        let mut node = StatusMap::new(&table, AlwaysSucceed::new());
+        assert_eq!(node.tick(&mut ()), Status::Running);
+    }
+
+    #[test]
+    fn finished_child_runs_again() {
+        let runs = Cell::new(0);
+        let child = Sequence::new(vec![InlineAction::new(|_| {
+            runs.set(runs.get() + 1);
+            Status::Succeeded
+        })]);
+        let mut node = StatusMap::new(&[(Status::Succeeded, Status::Running)], child);
+
+        for _ in 0..3 {
+            assert_eq!(node.tick(&mut ()), Status::Running);
+        }
+        assert_eq!(runs.get(), 3);
+    }
+
+    /// Returns a node that is running for `ticks` ticks and then returns
+    /// `status`, starting over when it is reset.
+    fn finish_after(ticks: u32, status: Status) -> Node<'static, ()> {
+        let mut count = 0;
+        InlineAction::new(move |_| {
+            count += 1;
+            if count > ticks {
+                count = 0;
+                status
+            } else {
+                Status::Running
+            }
+        })
+    }
+
+    #[test]
+    fn force_parity() {
+        let forced = |status: Status| -> Vec<(Node<'static, ()>, Node<'static, ()>)> {
+            vec![
+                (
+                    AlwaysSucceed::with_child(finish_after(2, status)),
+                    StatusMap::new(
+                        &[(Status::Failed, Status::Succeeded)],
+                        finish_after(2, status),
+                    ),
+                ),
+                (
+                    AlwaysFail::with_child(finish_after(2, status)),
+                    StatusMap::new(
+                        &[(Status::Succeeded, Status::Failed)],
+                        finish_after(2, status),
+                    ),
+                ),
+            ]
+        };
+
+        for &status in &[Status::Succeeded, Status::Failed] {
+            for (mut expected, mut actual) in forced(status) {
+                for _ in 0..2 {
+                    for _ in 0..3 {
+                        assert_eq!(actual.tick(&mut ()), expected.tick(&mut ()));
+                    }
+                    assert!(actual.status().unwrap().is_done());
+                    expected.reset();
+                    actual.reset();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reset_clears_failure() {
+        let mut internals = StatusMap {
+            child: AlwaysRunning::new(),
+            table: [Status::Failed, Status::Succeeded, Status::Failed],
+            failure: None,
+        };
+
+        assert_eq!(internals.tick(&mut ()), Status::Failed);
+        internals.reset();
+        assert!(internals.take_failure().is_none());
+    }
+}
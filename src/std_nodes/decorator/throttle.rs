@@ -0,0 +1,236 @@
+use crate::context::{self, Context};
+use crate::format::Value;
+use crate::node::{Node, Tickable};
+use crate::status::Status;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// A node that ticks its child at most once per period.
+///
+/// The first tick always ticks the child. Until the period has passed, as
+/// measured with the clock of the tree, every later tick returns the status
+/// from then without ticking the child. This is useful for conditions that
+/// are expensive to check and do not change quickly.
+///
+/// The cached status is kept when the node is reset, unless the child was
+/// running, so that a parent starting over does not check the child again
+/// too soon. A node created with `refresh_on_reset` drops the cache instead.
+///
+/// # State
+///
+/// **Initialized:** Before being ticked after either being reset or created.
+///
+/// **Running:** While the cached status of the child is running.
+///
+/// **Succeeded:** While the cached status of the child is successful.
+///
+/// **Failed:** While the cached status of the child is failed.
+///
+/// # Children
+///
+/// One. It is ticked at most once per period, and reset whenever the throttle
+/// node is reset or before it is ticked again after finishing.
+///
+/// # Examples
+///
+/// ```
+/// # use aspen::std_nodes::*;
+/// # use aspen::clock::MockClock;
+/// # use aspen::{BehaviorTree, Status};
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// let clock = Arc::new(MockClock::new());
+/// let check = Condition::new(|&ready: &bool| ready);
+/// let root = Throttle::new(Duration::from_secs(1), check);
+/// let mut tree = BehaviorTree::new(root);
+/// tree.set_clock(clock.clone());
+///
+/// let mut ready = false;
+/// assert_eq!(tree.tick(&mut ready), Status::Failed);
+///
+/// // The condition is not checked again until a second has passed
+/// ready = true;
+/// assert_eq!(tree.tick(&mut ready), Status::Failed);
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(tree.tick(&mut ready), Status::Succeeded);
+/// ```
+pub struct Throttle<'a, W> {
+    /// Child node.
+    child: Node<'a, W>,
+
+    /// The shortest time between two ticks of the child.
+    period: Duration,
+
+    /// Whether resetting this node drops the cached status.
+    refresh_on_reset: bool,
+
+    /// The status of the last tick of the child.
+    cached: Option<Status>,
+
+    /// When the child may be ticked again, unless the period goes past the
+    /// end of time.
+    next_tick: Option<Instant>,
+
+    /// The context of the tree this node is part of, if any.
+    context: Option<Rc<Context>>,
+}
+impl<'a, W> Throttle<'a, W>
+where
+    W: 'a,
+{
+    /// Creates a new Throttle node that ticks the child at most once per
+    /// `period`, keeping the cached status when it is reset.
+    pub fn new(period: Duration, child: Node<'a, W>) -> Node<'a, W> {
+        Throttle::build(period, false, child)
+    }
+
+    /// Creates a new Throttle node that ticks the child at most once per
+    /// `period`, and ticks it right away after being reset.
+    pub fn refresh_on_reset(period: Duration, child: Node<'a, W>) -> Node<'a, W> {
+        Throttle::build(period, true, child)
+    }
+
+    /// Creates the node with the given reset behavior.
+    pub(crate) fn build(
+        period: Duration,
+        refresh_on_reset: bool,
+        child: Node<'a, W>,
+    ) -> Node<'a, W> {
+        let internals = Throttle {
+            child,
+            period,
+            refresh_on_reset,
+            cached: None,
+            next_tick: None,
+            context: None,
+        };
+        Node::new(internals)
+    }
+}
+impl<'a, W> Tickable<W> for Throttle<'a, W> {
+    fn tick(&mut self, world: &mut W) -> Status {
+        let now = context::now(&self.context);
+        if let Some(status) = self.cached {
+            match self.next_tick {
+                Some(next_tick) if now >= next_tick => (),
+                _ => return status,
+            }
+        }
+
+        if self.child.status().is_some_and(|s| s.is_done()) {
+            self.child.reset();
+        }
+
+        let status = self.child.tick(world);
+        self.cached = Some(status);
+        self.next_tick = now.checked_add(self.period);
+        status
+    }
+
+    fn reset(&mut self) {
+        // A running child is halted, so its status no longer holds
+        if self.refresh_on_reset || self.cached == Some(Status::Running) {
+            self.cached = None;
+            self.next_tick = None;
+        }
+        self.child.reset();
+    }
+
+    fn children(&self) -> Vec<&Node<'_, W>> {
+        vec![&self.child]
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        f(&mut self.child)
+    }
+
+    fn attach(&mut self, context: &Rc<Context>) {
+        self.context = Some(context.clone());
+    }
+
+    fn params(&self) -> Vec<(String, Value)> {
+        let mut params = vec![(
+            "period".to_owned(),
+            Value::Number(self.period.as_secs_f64()),
+        )];
+        if self.refresh_on_reset {
+            params.push(("refresh_on_reset".to_owned(), Value::Bool(true)));
+        }
+        params
+    }
+
+    /// Returns the string "Throttle".
+    fn type_name(&self) -> &'static str {
+        "Throttle"
+    }
+}
+
+/// Convenience macro for creating Throttle nodes.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate aspen;
+/// # use std::time::Duration;
+/// # fn main() {
+/// let throttle = Throttle!{ Duration::from_millis(500),
+///     Condition!{ |&(a, b): &(u32, u32)| a < b }
+/// };
+/// # }
+/// ```
+#[macro_export]
+macro_rules! Throttle {
+    ( $d:expr, $e:expr ) => {
+        $crate::std_nodes::Throttle::new($d, $e)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bt::BehaviorTree;
+    use crate::clock::MockClock;
+    use crate::node::Tickable;
+    use crate::status::Status;
+    use crate::std_nodes::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn throttle_caches_status() {
+        let clock = Arc::new(MockClock::new());
+        let child = CountedTick::new(Status::Succeeded, 2, true);
+        let mut tree = BehaviorTree::new(Throttle::new(Duration::from_millis(100), child));
+        tree.set_clock(clock.clone());
+
+        assert_eq!(tree.tick(&mut ()), Status::Succeeded);
+        clock.advance(Duration::from_millis(99));
+        assert_eq!(tree.tick(&mut ()), Status::Succeeded);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(tree.tick(&mut ()), Status::Succeeded);
+
+        // The cache outlives a reset
+        tree.reset();
+        assert_eq!(tree.tick(&mut ()), Status::Succeeded);
+    }
+
+    #[test]
+    fn throttle_forever() {
+        let child = CountedTick::new(Status::Succeeded, 1, true);
+        let mut node = Throttle::new(Duration::MAX, child);
+
+        for _ in 0..3 {
+            assert_eq!(node.tick(&mut ()), Status::Succeeded);
+        }
+    }
+
+    #[test]
+    fn throttle_refresh_on_reset() {
+        let child = CountedTick::new(Status::Failed, 2, true);
+        let mut node = Throttle::refresh_on_reset(Duration::from_secs(60), child);
+
+        assert_eq!(node.tick(&mut ()), Status::Failed);
+        assert_eq!(node.tick(&mut ()), Status::Failed);
+        node.reset();
+        assert_eq!(node.tick(&mut ()), Status::Failed);
+    }
+}
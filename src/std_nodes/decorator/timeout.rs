@@ -0,0 +1,230 @@
+use crate::context::{self, Context};
+use crate::failure::FailureReason;
+use crate::format::Value;
+use crate::node::{Node, Tickable};
+use crate::status::Status;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// A node that fails if its child keeps running for too long.
+///
+/// The time is measured with the clock of the tree, starting from the first
+/// tick on which the child returned `Running`. Once the timeout has expired,
+/// the next tick halts the child instead of ticking it and fails with a
+/// reason.
+///
+/// # State
+///
+/// **Initialized:** Before being ticked after either being reset or created.
+///
+/// **Running:** While the child is running and the timeout has not expired.
+///
+/// **Succeeded:** When the child succeeds in time.
+///
+/// **Failed:** When the child fails in time, or when the timeout expires.
+///
+/// # Children
+///
+/// One. It is ticked or reset whenever the timeout node is ticked or reset,
+/// and halted when the timeout expires.
+///
+/// # Examples
+///
+/// A child that never finishes is stopped after the timeout:
+///
+/// ```
+/// # use aspen::std_nodes::*;
+/// # use aspen::clock::MockClock;
+/// # use aspen::node::Tickable;
+/// # use aspen::{BehaviorTree, Status};
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// let clock = Arc::new(MockClock::new());
+/// let root = Timeout::new(Duration::from_secs(2), AlwaysRunning::new());
+/// let mut tree = BehaviorTree::new(root);
+/// tree.set_clock(clock.clone());
+///
+/// assert_eq!(tree.tick(&mut ()), Status::Running);
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(tree.tick(&mut ()), Status::Running);
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(tree.tick(&mut ()), Status::Failed);
+/// assert_eq!(tree.root().children()[0].status(), None);
+/// ```
+pub struct Timeout<'a, W> {
+    /// Child node.
+    child: Node<'a, W>,
+
+    /// How long the child may run.
+    timeout: Duration,
+
+    /// When the child first returned `Running`, if it is running.
+    started: Option<Instant>,
+
+    /// Why this node failed on the last tick, if the timeout expired.
+    failure: Option<FailureReason>,
+
+    /// The context of the tree this node is part of, if any.
+    context: Option<Rc<Context>>,
+}
+impl<'a, W> Timeout<'a, W>
+where
+    W: 'a,
+{
+    /// Creates a new Timeout node that fails if the child runs for longer
+    /// than `timeout`.
+    pub fn new(timeout: Duration, child: Node<'a, W>) -> Node<'a, W> {
+        let internals = Timeout {
+            child,
+            timeout,
+            started: None,
+            failure: None,
+            context: None,
+        };
+        Node::new(internals)
+    }
+}
+impl<'a, W> Tickable<W> for Timeout<'a, W> {
+    fn tick(&mut self, world: &mut W) -> Status {
+        let now = context::now(&self.context);
+        if let Some(started) = self.started {
+            if now.saturating_duration_since(started) >= self.timeout {
+                debug!("Child timed out after {:?}", self.timeout);
+                self.child.halt();
+                self.started = None;
+                self.failure = Some(FailureReason::new(format!(
+                    "Timed out after {:?}",
+                    self.timeout
+                )));
+                return Status::Failed;
+            }
+        }
+
+        let status = self.child.tick(world);
+        if status == Status::Running {
+            self.started.get_or_insert(now);
+        } else {
+            self.started = None;
+        }
+
+        status
+    }
+
+    fn reset(&mut self) {
+        self.started = None;
+        self.failure = None;
+        self.child.reset();
+    }
+
+    fn children(&self) -> Vec<&Node<'_, W>> {
+        vec![&self.child]
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        f(&mut self.child)
+    }
+
+    fn attach(&mut self, context: &Rc<Context>) {
+        self.context = Some(context.clone());
+    }
+
+    fn take_failure(&mut self) -> Option<FailureReason> {
+        self.failure.take()
+    }
+
+    fn params(&self) -> Vec<(String, Value)> {
+        vec![(
+            "timeout".to_owned(),
+            Value::Number(self.timeout.as_secs_f64()),
+        )]
+    }
+
+    /// Returns the string "Timeout".
+    fn type_name(&self) -> &'static str {
+        "Timeout"
+    }
+}
+
+/// Convenience macro for creating Timeout nodes.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate aspen;
+/// # use std::time::Duration;
+/// # fn main() {
+/// let timeout = Timeout!{ Duration::from_millis(500),
+///     Condition!{ |&(a, b): &(u32, u32)| a < b }
+/// };
+/// # }
+/// ```
+#[macro_export]
+macro_rules! Timeout {
+    ( $d:expr, $e:expr ) => {
+        $crate::std_nodes::Timeout::new($d, $e)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Timeout;
+    use crate::bt::BehaviorTree;
+    use crate::clock::MockClock;
+    use crate::node::Tickable;
+    use crate::status::Status;
+    use crate::std_nodes::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn timeout_passes_through() {
+        let clock = Arc::new(MockClock::new());
+        let child = CountedTick::new(Status::Succeeded, 1, true);
+        let mut tree = BehaviorTree::new(Timeout::new(Duration::from_secs(1), child));
+        tree.set_clock(clock.clone());
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(tree.tick(&mut ()), Status::Succeeded);
+    }
+
+    #[test]
+    fn timeout_expires() {
+        let clock = Arc::new(MockClock::new());
+        let mut tree = BehaviorTree::new(Timeout::new(
+            Duration::from_millis(100),
+            AlwaysRunning::new().named(Some("spin")),
+        ));
+        tree.set_clock(clock.clone());
+
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        clock.advance(Duration::from_millis(99));
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(tree.tick(&mut ()), Status::Failed);
+        assert_eq!(
+            tree.root().failure_reason().unwrap().to_string(),
+            "Timeout: Timed out after 100ms"
+        );
+
+        // The timeout starts over once the tree is ticked again
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+    }
+
+    #[test]
+    fn reset_clears_failure() {
+        let mut internals = Timeout {
+            child: AlwaysRunning::new(),
+            timeout: Duration::default(),
+            started: None,
+            failure: None,
+            context: None,
+        };
+
+        assert_eq!(internals.tick(&mut ()), Status::Running);
+        assert_eq!(internals.tick(&mut ()), Status::Failed);
+        internals.reset();
+        assert!(internals.take_failure().is_none());
+    }
+}
@@ -12,19 +12,35 @@ mod parallel;
 pub use self::parallel::Parallel;
 
 mod decorator;
+pub use self::decorator::Backoff;
+pub use self::decorator::Cooldown;
 pub use self::decorator::Decorator;
+pub use self::decorator::Delay;
 pub use self::decorator::Invert;
 pub use self::decorator::Repeat;
+pub use self::decorator::Retry;
+pub use self::decorator::StatusMap;
+pub use self::decorator::Throttle;
+pub use self::decorator::Timeout;
 pub use self::decorator::UntilFail;
 pub use self::decorator::UntilSuccess;
 
 mod action;
 pub use self::action::Action;
+pub use self::action::AsyncAction;
+pub use self::action::CancelToken;
 pub use self::action::InlineAction;
+pub use self::action::ProgressReporter;
 
 mod condition;
 pub use self::condition::Condition;
 
+mod subtree;
+pub use self::subtree::SubTree;
+
+mod wait;
+pub use self::wait::Wait;
+
 mod constants;
 pub use self::constants::AlwaysFail;
 pub use self::constants::AlwaysRunning;
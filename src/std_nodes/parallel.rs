@@ -1,4 +1,5 @@
 //! Nodes that tick their children in parallel
+use crate::format::Value;
 use crate::node::{Node, Tickable};
 use crate::status::Status;
 
@@ -162,6 +163,17 @@ impl<'a, W> Tickable<W> for Parallel<'a, W> {
         self.children.iter().collect()
     }
 
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        self.children.iter_mut().for_each(f)
+    }
+
+    fn params(&self) -> Vec<(String, Value)> {
+        vec![(
+            "required_successes".to_owned(),
+            Value::Number(self.required_successes as f64),
+        )]
+    }
+
     /// Returns the string "Parallel".
     fn type_name(&self) -> &'static str {
         "Parallel"
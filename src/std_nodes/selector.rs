@@ -35,7 +35,7 @@ use crate::Status;
 /// ticked as long as all the sibling nodes to the left failed.
 ///
 /// Note that, if a node is running and a sibling to the left returned either
-/// success or running, the child node will be reset. Additionally, the children
+/// success or running, the child node will be halted. Additionally, the children
 /// will be reset each time the parent node is reset.
 ///
 /// # Examples
@@ -104,7 +104,7 @@ impl<'a, W> Tickable<W> for Selector<'a, W> {
             // is either running or successful. If we find either of those, all
             // children after that node need to be reset
             if ret_status != Status::Failed {
-                child.reset()
+                child.halt()
             } else {
                 ret_status = child.tick(world);
             }
@@ -125,6 +125,10 @@ impl<'a, W> Tickable<W> for Selector<'a, W> {
         self.children.iter().collect()
     }
 
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        self.children.iter_mut().for_each(f)
+    }
+
     /// Returns the string "Selector".
     fn type_name(&self) -> &'static str {
         "Selector"
@@ -239,7 +243,7 @@ pub struct StatefulSelector<'a, W> {
 }
 impl<'a, W> StatefulSelector<'a, W>
 where
-    W: Clone + 'a,
+    W: 'a,
 {
     /// Creates a new StatefulSelector node from a vector of Nodes.
     pub fn new(children: Vec<Node<'a, W>>) -> Node<'a, W> {
@@ -250,10 +254,7 @@ where
         Node::new(internals)
     }
 }
-impl<'a, W> Tickable<W> for StatefulSelector<'a, W>
-where
-    W: Clone,
-{
+impl<'a, W> Tickable<W> for StatefulSelector<'a, W> {
     fn tick(&mut self, world: &mut W) -> Status {
         // Tick the children as long as they keep failing
         let mut ret_status = Status::Failed;
@@ -281,9 +282,13 @@ where
         self.children.iter().collect()
     }
 
-    /// Returns the string "Selector".
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        self.children.iter_mut().for_each(f)
+    }
+
+    /// Returns the string "StatefulSelector".
     fn type_name(&self) -> &'static str {
-        "Selector"
+        "StatefulSelector"
     }
 }
 
@@ -305,7 +310,7 @@ where
 macro_rules! StatefulSelector
 {
 	( $( $e:expr ),* ) => {
-		$crate::std_nodes::Selector::new(vec![$( $e ),*])
+		$crate::std_nodes::StatefulSelector::new(vec![$( $e ),*])
 	};
 }
 
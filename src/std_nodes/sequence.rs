@@ -35,7 +35,7 @@ use crate::Status;
 /// ticked as long as all the sibling nodes to the left succeeded.
 ///
 /// Note that, if a node is running and a sibling to the left returned either
-/// failure or running, the child node will be reset. Additionally, the children
+/// failure or running, the child node will be halted. Additionally, the children
 /// will be reset each time the parent is.
 ///
 /// # Examples
@@ -120,7 +120,7 @@ impl<'a, W> Tickable<W> for ActiveSequence<'a, W> {
             if ret_status == Status::Succeeded {
                 ret_status = child.tick(world);
             } else {
-                child.reset();
+                child.halt();
             }
         }
 
@@ -139,6 +139,10 @@ impl<'a, W> Tickable<W> for ActiveSequence<'a, W> {
         self.children.iter().collect()
     }
 
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        self.children.iter_mut().for_each(f)
+    }
+
     /// Returns the string "ActiveSequence".
     fn type_name(&self) -> &'static str {
         "ActiveSequence"
@@ -288,6 +292,10 @@ impl<'a, W> Tickable<W> for Sequence<'a, W> {
         self.children.iter().collect()
     }
 
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        self.children.iter_mut().for_each(f)
+    }
+
     /// Returns the string "Sequence".
     fn type_name(&self) -> &'static str {
         "Sequence"
@@ -451,4 +459,46 @@ mod tests {
         // Make sure we got the expected value
         assert_eq!(status, Status::Failed);
     }
+
+    #[test]
+    fn check_active_halts_preempted() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        /// Runs until halted, counting how many times that happens.
+        struct Halting(Rc<Cell<u32>>);
+        impl Tickable<bool> for Halting {
+            fn tick(&mut self, _: &mut bool) -> Status {
+                Status::Running
+            }
+
+            fn reset(&mut self) {
+                panic!("A running node should be halted, not reset");
+            }
+
+            fn halt(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+
+            fn type_name(&self) -> &'static str {
+                "Halting"
+            }
+        }
+
+        let halts = Rc::new(Cell::new(0));
+        let mut node = ActiveSequence::new()
+            .with_child(Condition::new(|ok: &bool| *ok))
+            .with_child(Halting(halts.clone()))
+            .into_node();
+
+        let mut ok = true;
+        assert_eq!(node.tick(&mut ok), Status::Running);
+        assert_eq!(halts.get(), 0);
+
+        // The condition now fails, so the running child is preempted
+        ok = false;
+        assert_eq!(node.tick(&mut ok), Status::Failed);
+        assert_eq!(halts.get(), 1);
+        assert_eq!(node.children()[1].status(), None);
+    }
 }
@@ -0,0 +1,217 @@
+//! Nodes that give their children a scope of their own on the blackboard.
+use crate::blackboard::{HasBlackboard, Scope};
+use crate::format::Value;
+use crate::node::{Node, Tickable};
+use crate::status::Status;
+
+/// A node that ticks its child within its own blackboard scope.
+///
+/// Every time this node is ticked, it pushes its scope onto the world's
+/// blackboard, ticks the child, and then pops the scope again. Entries that
+/// the child writes stay in this node's scope between ticks and are cleared
+/// when this node is reset. Names can be remapped to entries in the enclosing
+/// scope, which allows the same subtree to be reused with different inputs and
+/// outputs.
+///
+/// # State
+///
+/// **Initialized:** Before being ticked after either being created or reset.
+///
+/// **Running:** While the child is running.
+///
+/// **Succeeded:** When the child succeeds.
+///
+/// **Failed:** When the child fails.
+///
+/// # Children
+///
+/// One, which is ticked or reset whenever this node is ticked or reset.
+///
+/// # Examples
+///
+/// A subtree that reads the parent's `target_pose` as `goal`:
+///
+/// ```
+/// # use aspen::std_nodes::*;
+/// # use aspen::Status;
+/// # use aspen::node::Tickable;
+/// # use aspen::blackboard::{Blackboard, Key};
+/// let pose = Key::<u32>::new("target_pose");
+/// let goal = Key::<u32>::new("goal");
+///
+/// let mut board = Blackboard::new();
+/// board.set(&pose, 12);
+///
+/// let child = Condition::with_key(goal, |&g| g == 12);
+/// let mut node = SubTree::with_remap(&[("goal", "target_pose")], child);
+/// assert_eq!(node.tick(&mut board), Status::Succeeded);
+/// ```
+pub struct SubTree<'a, W> {
+    /// Child node.
+    child: Node<'a, W>,
+
+    /// The scope that is pushed while the child is ticked.
+    scope: Scope,
+}
+impl<'a, W> SubTree<'a, W>
+where
+    W: HasBlackboard + 'a,
+{
+    /// Creates a new `SubTree` node with an empty scope.
+    pub fn new(child: Node<'a, W>) -> Node<'a, W> {
+        SubTree::with_remap(&[], child)
+    }
+
+    /// Creates a new `SubTree` node that forwards each local name in `remap`
+    /// to the paired name in the enclosing scope.
+    pub fn with_remap(remap: &[(&str, &str)], child: Node<'a, W>) -> Node<'a, W> {
+        let scope = remap.iter().fold(Scope::new(), |scope, &(local, parent)| {
+            scope.remap(local, parent)
+        });
+        let internals = SubTree { child, scope };
+        Node::new(internals)
+    }
+}
+impl<'a, W> Tickable<W> for SubTree<'a, W>
+where
+    W: HasBlackboard,
+{
+    fn tick(&mut self, world: &mut W) -> Status {
+        let scope = std::mem::take(&mut self.scope);
+        world.blackboard_mut().push_scope(scope);
+
+        let status = self.child.tick(world);
+
+        self.scope = world
+            .blackboard_mut()
+            .pop_scope()
+            .expect("Child popped the SubTree scope");
+        status
+    }
+
+    fn reset(&mut self) {
+        self.scope.clear();
+        self.child.reset();
+    }
+
+    fn children(&self) -> Vec<&Node<W>> {
+        vec![&self.child]
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, W>)) {
+        f(&mut self.child)
+    }
+
+    fn params(&self) -> Vec<(String, Value)> {
+        let remap = self.scope.remapping();
+        if remap.is_empty() {
+            return Vec::new();
+        }
+
+        let remap = remap
+            .into_iter()
+            .map(|(l, p)| (l.to_owned(), Value::String(p.to_owned())))
+            .collect();
+        vec![("remap".to_owned(), Value::Object(remap))]
+    }
+
+    /// Returns the string "SubTree".
+    fn type_name(&self) -> &'static str {
+        "SubTree"
+    }
+}
+
+/// Convenience macro for creating SubTree nodes.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate aspen;
+/// # use aspen::blackboard::{Blackboard, Key};
+/// # fn main() {
+/// let subtree = SubTree!{ "goal" => "target_pose", "speed" => "max_speed";
+///     Condition!{ |b: &Blackboard| b.contains(&Key::<u32>::new("goal")) }
+/// };
+/// # }
+/// ```
+#[macro_export]
+macro_rules! SubTree {
+    ( $( $l:expr => $p:expr ),* ; $e:expr ) => {
+        $crate::std_nodes::SubTree::with_remap(&[$( ($l, $p) ),*], $e)
+    };
+    ( $e:expr ) => {
+        $crate::std_nodes::SubTree::new($e)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blackboard::{Blackboard, Key};
+    use crate::node::Tickable;
+    use crate::status::Status;
+    use crate::std_nodes::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn scope_is_local() {
+        let key = Key::<u32>::new("value");
+        let mut board = Blackboard::new();
+
+        let seen = Cell::new(0);
+        let child = InlineAction::new(|b: &mut Blackboard| {
+            let value = b.get(&Key::<u32>::new("value")).cloned().unwrap_or(0);
+            seen.set(value);
+            b.set(&Key::new("value"), value + 1);
+            Status::Running
+        });
+        let mut node = SubTree::new(child);
+
+        node.tick(&mut board);
+        node.tick(&mut board);
+        assert_eq!(seen.get(), 1);
+        assert_eq!(board.get(&key), None);
+        assert_eq!(board.depth(), 1);
+
+        // The scope survived between ticks but not the reset, so the child
+        // reads the value of the parent scope instead of its own
+        board.set(&key, 10);
+        node.reset();
+        node.tick(&mut board);
+        assert_eq!(seen.get(), 10);
+        assert_eq!(board.get(&key), Some(&10));
+    }
+
+    #[test]
+    fn remap_writes_to_parent() {
+        let out = Key::<u32>::new("out");
+        let mut board = Blackboard::new();
+
+        let child = InlineAction::map_key(Key::<u32>::new("in"), Key::new("result"), |&x| {
+            Some(x * 2)
+        });
+        let mut node = SubTree::with_remap(&[("result", "out"), ("in", "input")], child);
+
+        // Missing input fails
+        assert_eq!(node.tick(&mut board), Status::Failed);
+
+        board.set(&Key::new("input"), 21u32);
+        node.reset();
+        assert_eq!(node.tick(&mut board), Status::Succeeded);
+        assert_eq!(board.get(&out), Some(&42));
+    }
+
+    #[test]
+    fn describe_remapping() {
+        let node: crate::node::Node<Blackboard> = SubTree!{ "b" => "y", "a" => "x";
+            AlwaysSucceed::new()
+        };
+        assert_eq!(
+            node.describe().to_string(),
+            concat!(
+                "{ \"type\": \"SubTree\", \"params\": { \"remap\": { \"a\": \"x\", \"b\": \"y\" } }, \"children\": [\n",
+                "    { \"type\": \"AlwaysSucceed\" }\n",
+                "] }"
+            )
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Nodes that wait.
+use crate::context::{self, Context};
+use crate::format::Value;
+use crate::node::{Node, Tickable};
+use crate::status::Status;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// How long a `Wait` node waits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Until {
+    /// A time, measured with the clock of the tree.
+    Elapsed(Duration),
+
+    /// A number of ticks that return `Running`.
+    Ticks(u32),
+}
+
+/// A node that is running for a while and then succeeds.
+///
+/// The wait starts on the first tick after the node is created or reset. It
+/// can either last for a time, measured with the clock of the tree, or for a
+/// number of ticks.
+///
+/// # State
+///
+/// **Initialized:** Before being ticked after either being created or reset.
+///
+/// **Running:** Until the time has passed or the ticks have been counted.
+///
+/// **Succeeded:** Once the wait is over.
+///
+/// **Failed:** Never.
+///
+/// # Children
+///
+/// None
+///
+/// # Examples
+///
+/// ```
+/// # use aspen::std_nodes::*;
+/// # use aspen::Status;
+/// # use aspen::node::Tickable;
+/// let mut node = Wait::ticks(2);
+///
+/// assert_eq!(node.tick(&mut ()), Status::Running);
+/// assert_eq!(node.tick(&mut ()), Status::Running);
+/// assert_eq!(node.tick(&mut ()), Status::Succeeded);
+/// ```
+pub struct Wait {
+    /// When the wait is over.
+    until: Until,
+
+    /// When the wait started, if it has.
+    started: Option<Instant>,
+
+    /// The number of ticks so far.
+    ticks: u32,
+
+    /// The context of the tree this node is part of, if any.
+    context: Option<Rc<Context>>,
+}
+impl Wait {
+    /// Creates a new Wait node that is running until `duration` has passed.
+    pub fn new<W>(duration: Duration) -> Node<'static, W> {
+        Wait::build(Until::Elapsed(duration))
+    }
+
+    /// Creates a new Wait node that is running for `ticks` ticks.
+    pub fn ticks<W>(ticks: u32) -> Node<'static, W> {
+        Wait::build(Until::Ticks(ticks))
+    }
+
+    /// Creates the node that waits until the given condition.
+    fn build<W>(until: Until) -> Node<'static, W> {
+        let internals = Wait {
+            until,
+            started: None,
+            ticks: 0,
+            context: None,
+        };
+        Node::new(internals)
+    }
+}
+impl<W> Tickable<W> for Wait {
+    fn tick(&mut self, _: &mut W) -> Status {
+        let done = match self.until {
+            Until::Elapsed(duration) => {
+                let now = context::now(&self.context);
+                let started = *self.started.get_or_insert(now);
+                now.saturating_duration_since(started) >= duration
+            }
+            Until::Ticks(ticks) => {
+                let done = self.ticks >= ticks;
+                self.ticks += 1;
+                done
+            }
+        };
+
+        if done {
+            Status::Succeeded
+        } else {
+            Status::Running
+        }
+    }
+
+    fn reset(&mut self) {
+        self.started = None;
+        self.ticks = 0;
+    }
+
+    fn attach(&mut self, context: &Rc<Context>) {
+        self.context = Some(context.clone());
+    }
+
+    fn params(&self) -> Vec<(String, Value)> {
+        match self.until {
+            Until::Elapsed(duration) => {
+                vec![("duration".to_owned(), Value::Number(duration.as_secs_f64()))]
+            }
+            Until::Ticks(ticks) => vec![("ticks".to_owned(), Value::Number(ticks.into()))],
+        }
+    }
+
+    /// Returns the string "Wait".
+    fn type_name(&self) -> &'static str {
+        "Wait"
+    }
+}
+
+/// Convenience macro for creating Wait nodes.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate aspen;
+/// # use std::time::Duration;
+/// # fn main() {
+/// let wait: aspen::node::Node<()> = Wait!{ Duration::from_millis(500) };
+/// # }
+/// ```
+#[macro_export]
+macro_rules! Wait {
+    ( $d:expr ) => {
+        $crate::std_nodes::Wait::new($d)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bt::BehaviorTree;
+    use crate::clock::MockClock;
+    use crate::node::Tickable;
+    use crate::status::Status;
+    use crate::std_nodes::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn wait_duration() {
+        let clock = Arc::new(MockClock::new());
+        let mut tree = BehaviorTree::new(Wait::new(Duration::from_millis(100)));
+        tree.set_clock(clock.clone());
+
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        clock.advance(Duration::from_millis(99));
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(tree.tick(&mut ()), Status::Succeeded);
+
+        // The tree resets the finished root, which starts the wait over
+        assert_eq!(tree.tick(&mut ()), Status::Running);
+    }
+
+    #[test]
+    fn wait_ticks_reset() {
+        let mut node = Wait::ticks(1);
+
+        assert_eq!(node.tick(&mut ()), Status::Running);
+        node.reset();
+        assert_eq!(node.tick(&mut ()), Status::Running);
+        assert_eq!(node.tick(&mut ()), Status::Succeeded);
+
+        let mut node = Wait::ticks(0);
+        assert_eq!(node.tick(&mut ()), Status::Succeeded);
+    }
+}
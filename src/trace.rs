@@ -0,0 +1,633 @@
+//! Recording tree executions to a file and replaying them later.
+//!
+//! A `Recorder` is an observer that writes every status transition of every
+//! node in a tree to a trace in the JSON lines format. The first line of the
+//! trace is the description of the tree, as written by `NodeDesc::to_value`.
+//! Every following line either marks the start of a tick or is a single
+//! transition:
+//!
+//! ```json
+//! { "tick": 3, "time": 1718049273.5107 }
+//! { "tick": 3, "time": 1718049273.5121, "node": 4, "old": "Running", "new": "Succeeded" }
+//! ```
+//!
+//! `tick` counts the ticks of the tree from one, `time` is the number of
+//! seconds since the Unix epoch and `node` is the `NodeId` of the node. When a
+//! node is reset its `new` status is `null`.
+//!
+//! A `Replay` reads a trace back and rebuilds a copy of the tree that steps
+//! through the recorded statuses one tick at a time. The copy has no logic
+//! and does not need a world, but it can be printed and exported like any
+//! other tree.
+//!
+//! # Examples
+//!
+//! ```
+//! # use aspen::std_nodes::*;
+//! # use aspen::BehaviorTree;
+//! # use aspen::trace::{Recorder, Replay};
+//! # use aspen::Status;
+//! # use std::cell::RefCell;
+//! # use std::io::{self, Write};
+//! # use std::rc::Rc;
+//! # #[derive(Clone)]
+//! # struct Buffer(Rc<RefCell<Vec<u8>>>);
+//! # impl Write for Buffer {
+//! #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.borrow_mut().write(buf) }
+//! #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+//! # }
+//! # let buffer = Buffer(Rc::new(RefCell::new(Vec::new())));
+//! # let file = buffer.clone();
+//! let root = Sequence::new(vec![AlwaysSucceed::new(), AlwaysRunning::new()]);
+//! let mut tree = BehaviorTree::new(root);
+//! let recorder = Recorder::new(file, &tree).unwrap();
+//! tree.add_observer(recorder);
+//! tree.tick(&mut ());
+//! tree.tick(&mut ());
+//! drop(tree);
+//!
+//! # let text = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+//! let mut replay = Replay::parse(&text).unwrap();
+//! assert_eq!(replay.ticks(), 2);
+//! assert!(replay.step());
+//! assert_eq!(replay.root().status(), Some(Status::Running));
+//! assert_eq!(replay.find("Sequence/AlwaysSucceed").unwrap().status(), Some(Status::Succeeded));
+//! ```
+use crate::bt::BehaviorTree;
+use crate::context::Context;
+use crate::format::{NodeDesc, Position, Value};
+use crate::graph;
+use crate::loader::{LoadError, LoadErrorKind};
+use crate::node::{self, Node, NodeId, Tickable};
+use crate::observer::{NodeInfo, TreeObserver};
+use crate::pretty::Pretty;
+use crate::status::Status;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A single status transition from a trace.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TraceEvent {
+    /// The tick of the tree during which the transition happened.
+    pub tick: u64,
+
+    /// When the transition happened.
+    pub time: SystemTime,
+
+    /// The node whose status changed.
+    pub node: NodeId,
+
+    /// The status of the node before the transition.
+    pub old: Option<Status>,
+
+    /// The status of the node after the transition.
+    pub new: Option<Status>,
+}
+impl TraceEvent {
+    /// Converts the event into the JSON object that is written to a trace.
+    fn to_value(self) -> Value {
+        Value::Object(vec![
+            ("tick".to_owned(), Value::Number(self.tick as f64)),
+            (
+                "time".to_owned(),
+                Value::Number(seconds_since_epoch(self.time)),
+            ),
+            ("node".to_owned(), Value::Number(self.node.index() as f64)),
+            ("old".to_owned(), status_value(self.old)),
+            ("new".to_owned(), status_value(self.new)),
+        ])
+    }
+
+    /// Reads one line of a trace, returning its tick and the transition if
+    /// the line is not just the start of a tick.
+    fn from_value(value: &Value, nodes: usize) -> Result<(u64, Option<TraceEvent>), String> {
+        let fields = value.as_object().ok_or("Expected an event object")?;
+        let field = |key: &str| {
+            fields
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v)
+                .ok_or(format!("Event is missing \"{}\"", key))
+        };
+        let status = |key: &str| match field(key)? {
+            Value::Null => Ok(None),
+            v => v
+                .as_str()
+                .and_then(|s| s.parse().ok())
+                .map(Some)
+                .ok_or(format!("Expected \"{}\" to be a status or null", key)),
+        };
+
+        let tick = field("tick")?
+            .as_u64()
+            .ok_or("Expected \"tick\" to be a non-negative integer")?;
+        let time = field("time")?
+            .as_f64()
+            .and_then(|t| Duration::try_from_secs_f64(t).ok())
+            .and_then(|t| UNIX_EPOCH.checked_add(t))
+            .ok_or("Expected \"time\" to be a non-negative number")?;
+        if !fields.iter().any(|(k, _)| k == "node") {
+            return Ok((tick, None));
+        }
+
+        let node = field("node")?
+            .as_u64()
+            .filter(|n| (*n as usize) < nodes)
+            .ok_or("Expected \"node\" to be the ID of a node in the tree")?;
+
+        let event = TraceEvent {
+            tick,
+            time,
+            node: NodeId::new(node as usize),
+            old: status("old")?,
+            new: status("new")?,
+        };
+        Ok((tick, Some(event)))
+    }
+}
+
+/// Returns the number of seconds since the Unix epoch.
+fn seconds_since_epoch(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// Returns the value that represents a status in a trace.
+fn status_value(status: Option<Status>) -> Value {
+    match status {
+        Some(s) => Value::String(format!("{:?}", s)),
+        None => Value::Null,
+    }
+}
+
+/// An observer that writes every status transition in a tree to a trace.
+///
+/// The recorder should be added to the tree before it is first ticked, as
+/// statuses from before that are not known to it.
+///
+/// Errors while writing are logged once, after which the recorder stops
+/// writing. The writer is not flushed until the recorder is dropped, which
+/// happens when the tree it was added to is dropped.
+pub struct Recorder<T: Write> {
+    /// Where the trace is written.
+    out: T,
+
+    /// The tick the tree is currently in.
+    tick: u64,
+
+    /// The last known status of every node that has one.
+    statuses: HashMap<NodeId, Status>,
+
+    /// Whether writing to the trace has failed.
+    failed: bool,
+}
+impl<T: Write> Recorder<T> {
+    /// Creates a recorder for the given tree and writes the tree's
+    /// description as the first line of the trace.
+    ///
+    /// The recorder must then be added to the same tree as an observer.
+    pub fn new<W>(mut out: T, tree: &BehaviorTree<W>) -> io::Result<Recorder<T>> {
+        writeln!(out, "{}", tree.describe().to_value())?;
+        Ok(Recorder {
+            out,
+            tick: tree.ticks(),
+            statuses: HashMap::new(),
+            failed: false,
+        })
+    }
+
+    /// Writes a transition to the trace.
+    fn record(&mut self, node: NodeId, old: Option<Status>, new: Option<Status>) {
+        let event = TraceEvent {
+            tick: self.tick,
+            time: SystemTime::now(),
+            node,
+            old,
+            new,
+        };
+        self.write(event.to_value());
+    }
+
+    /// Writes a line to the trace.
+    fn write(&mut self, line: Value) {
+        if self.failed {
+            return;
+        }
+
+        if let Err(e) = writeln!(self.out, "{}", line) {
+            warn!("Unable to write to trace, recording stopped: {}", e);
+            self.failed = true;
+        }
+    }
+}
+impl<T: Write> TreeObserver for Recorder<T> {
+    fn on_tree_tick(&mut self, tick: u64) {
+        self.tick = tick;
+        self.write(Value::Object(vec![
+            ("tick".to_owned(), Value::Number(tick as f64)),
+            (
+                "time".to_owned(),
+                Value::Number(seconds_since_epoch(SystemTime::now())),
+            ),
+        ]));
+    }
+
+    fn on_status_change(&mut self, node: &NodeInfo, old: Option<Status>, new: Status) {
+        self.statuses.insert(node.id(), new);
+        self.record(node.id(), old, Some(new));
+    }
+
+    fn on_reset(&mut self, node: &NodeInfo) {
+        let old = self.statuses.remove(&node.id());
+        self.record(node.id(), old, None);
+    }
+
+    fn on_halt(&mut self, node: &NodeInfo) {
+        self.on_reset(node);
+    }
+}
+impl<T: Write> Drop for Recorder<T> {
+    fn drop(&mut self) {
+        if let Err(e) = self.out.flush() {
+            warn!("Unable to flush trace: {}", e);
+        }
+    }
+}
+
+/// A node standing in for a recorded node during a replay.
+struct Recorded {
+    /// The type name of the recorded node.
+    type_name: String,
+
+    /// The parameters of the recorded node.
+    params: Vec<(String, Value)>,
+
+    /// The stand-ins for the children of the recorded node.
+    children: Vec<Node<'static, ()>>,
+}
+impl Recorded {
+    /// Builds the stand-in for the described node and its children.
+    fn build(desc: &NodeDesc) -> Node<'static, ()> {
+        let node = Node::new(Recorded {
+            type_name: desc.type_name.clone(),
+            params: desc.params.clone(),
+            children: desc.children.iter().map(Recorded::build).collect(),
+        });
+
+        node.named(desc.name.as_ref())
+    }
+}
+impl Tickable<()> for Recorded {
+    fn tick(&mut self, _: &mut ()) -> Status {
+        // The tree of a replay is never handed out mutably
+        unreachable!("Recorded nodes can not be ticked")
+    }
+
+    fn reset(&mut self) {
+        for child in self.children.iter_mut() {
+            child.reset();
+        }
+    }
+
+    fn children(&self) -> Vec<&Node<'_, ()>> {
+        self.children.iter().collect()
+    }
+
+    fn for_each_child_mut(&mut self, f: &mut dyn FnMut(&mut Node<'_, ()>)) {
+        self.children.iter_mut().for_each(f)
+    }
+
+    fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    fn params(&self) -> Vec<(String, Value)> {
+        self.params.clone()
+    }
+}
+
+/// Sets the status of the node with the given ID.
+fn set_status(node: &mut Node<()>, id: NodeId, status: Option<Status>) {
+    if node.id() == Some(id) {
+        node.set_status(status);
+        return;
+    }
+
+    node.for_each_child_mut(&mut |child| set_status(child, id, status));
+}
+
+/// Clears the status of the node and all of its descendants.
+fn clear(node: &mut Node<()>) {
+    node.set_status(None);
+    node.for_each_child_mut(&mut |child| clear(child));
+}
+
+/// A recorded execution of a tree that can be stepped through tick by tick.
+pub struct Replay {
+    /// A copy of the recorded tree that holds the replayed statuses.
+    root: Node<'static, ()>,
+
+    /// Every transition in the trace, in order.
+    events: Vec<TraceEvent>,
+
+    /// The number of ticks in the trace.
+    ticks: u64,
+
+    /// The index of the first event that has not been replayed.
+    next: usize,
+
+    /// The last tick that was replayed.
+    tick: u64,
+}
+impl Replay {
+    /// Reads a trace from a file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Replay, LoadError> {
+        let text =
+            fs::read_to_string(path).map_err(|e| LoadError::new(None, LoadErrorKind::Io(e)))?;
+        Replay::parse(&text)
+    }
+
+    /// Reads a trace from text.
+    ///
+    /// Errors are reported at the line of the trace they were found on.
+    pub fn parse(text: &str) -> Result<Replay, LoadError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty());
+
+        // Positions within a line are moved to the line they were read from
+        let error = |line: usize, e: crate::format::ParseError| {
+            let position = Position {
+                line: line + 1,
+                column: e.position().column,
+            };
+            LoadError::new(Some(position), LoadErrorKind::Parse(e))
+        };
+        let custom = |line: usize, msg: String| {
+            let position = Position {
+                line: line + 1,
+                column: 1,
+            };
+            LoadError::new(Some(position), LoadErrorKind::Custom(msg))
+        };
+
+        let (_, header) = lines.next().ok_or_else(|| {
+            LoadError::new(None, LoadErrorKind::Custom("Trace is empty".to_owned()))
+        })?;
+        let desc = NodeDesc::parse(header).map_err(|e| error(0, e))?;
+
+        let mut root = Recorded::build(&desc);
+        let path = node::path_segment(root.name());
+        root.attach_tree(&Rc::new(Context::default()), path, &mut 0);
+        let nodes = count(&root);
+
+        let mut events = Vec::new();
+        let mut ticks = 0;
+        for (i, line) in lines {
+            let value = Value::parse(line).map_err(|e| error(i, e))?;
+            let (tick, event) =
+                TraceEvent::from_value(&value, nodes).map_err(|msg| custom(i, msg))?;
+            if tick < ticks {
+                return Err(custom(i, "Events are not in tick order".to_owned()));
+            }
+
+            ticks = tick;
+            events.extend(event);
+        }
+
+        Ok(Replay {
+            root,
+            events,
+            ticks,
+            next: 0,
+            tick: 0,
+        })
+    }
+
+    /// Returns the copy of the recorded tree, holding the statuses as of the
+    /// last replayed tick.
+    pub fn root(&self) -> &Node<'_, ()> {
+        &self.root
+    }
+
+    /// Returns the node with the given path in the copy of the recorded tree.
+    pub fn find(&self, path: &str) -> Option<&Node<'_, ()>> {
+        self.root.find(path)
+    }
+
+    /// Returns every transition in the trace.
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    /// Returns the number of ticks in the trace.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Returns the last tick that was replayed, or zero if none has been.
+    pub fn current_tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Replays the transitions of the next tick.
+    ///
+    /// Returns `false`, and does nothing, if every tick has been replayed.
+    pub fn step(&mut self) -> bool {
+        if self.tick >= self.ticks() {
+            return false;
+        }
+
+        self.tick += 1;
+        while let Some(event) = self.events.get(self.next) {
+            if event.tick > self.tick {
+                break;
+            }
+
+            set_status(&mut self.root, event.node, event.new);
+            self.next += 1;
+        }
+
+        true
+    }
+
+    /// Replays the trace from the beginning up to and including the given
+    /// tick, which may be before the current one.
+    pub fn seek(&mut self, tick: u64) {
+        if tick < self.tick {
+            clear(&mut self.root);
+            self.next = 0;
+            self.tick = 0;
+        }
+
+        while self.tick < tick && self.step() {}
+    }
+
+    /// Returns a printer that writes the tree with one node per line.
+    pub fn pretty(&self) -> Pretty<'_, 'static, ()> {
+        Pretty::new(&self.root)
+    }
+
+    /// Renders the tree, colored by the replayed statuses, as a Graphviz DOT
+    /// digraph.
+    pub fn to_dot(&self) -> String {
+        graph::to_dot(&self.root)
+    }
+
+    /// Renders the tree, colored by the replayed statuses, as a Mermaid
+    /// flowchart.
+    pub fn to_mermaid(&self) -> String {
+        graph::to_mermaid(&self.root)
+    }
+}
+impl fmt::Display for Replay {
+    /// Writes the tree as of the last replayed tick, in the same formats as
+    /// `BehaviorTree`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return self.pretty().fmt(f);
+        }
+
+        write!(f, "Replay:( tick = {}, root = {} )", self.tick, self.root)
+    }
+}
+
+/// Returns the number of nodes in the tree.
+fn count(node: &Node<()>) -> usize {
+    1 + node.children().into_iter().map(count).sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std_nodes::*;
+    use std::cell::RefCell;
+
+    /// A writer that can be read while a recorder owns a copy of it.
+    #[derive(Clone, Default)]
+    struct Buffer(Rc<RefCell<Vec<u8>>>);
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    impl Buffer {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let root = Selector::new(vec![
+            AlwaysFail::new().named(Some("first")),
+            Sequence::new(vec![
+                CountedTick::new(Status::Succeeded, 2, true),
+                AlwaysFail::new(),
+            ]),
+        ]);
+        let mut tree = BehaviorTree::new(root.named(Some("root")));
+        let buffer = Buffer::default();
+        let recorder = Recorder::new(buffer.clone(), &tree).unwrap();
+        tree.add_observer(recorder);
+
+        assert_eq!(tree.tick(&mut ()), Status::Failed);
+        assert_eq!(tree.tick(&mut ()), Status::Failed);
+        let expected = format!("{:#}", tree);
+        drop(tree);
+
+        let text = buffer.text();
+        assert!(text.starts_with("{ \"type\": \"Selector\", \"name\": \"root\", \"children\": ["));
+        assert!(text.contains("\"tick\": 2, "));
+        assert!(text.contains("\"node\": 1, \"old\": \"Failed\", \"new\": null }"));
+
+        let mut replay = Replay::parse(&text).unwrap();
+        assert_eq!(replay.ticks(), 2);
+        assert_eq!(replay.root().status(), None);
+        assert!(replay.step());
+        assert_eq!(replay.current_tick(), 1);
+        assert_eq!(replay.root().status(), Some(Status::Failed));
+        assert!(replay.step());
+        assert!(!replay.step());
+        assert_eq!(format!("{:#}", replay), expected);
+
+        replay.seek(0);
+        assert_eq!(replay.root().status(), None);
+        assert_eq!(replay.find("root/first").unwrap().status(), None);
+        replay.seek(1);
+        assert_eq!(
+            replay.find("root/first").unwrap().status(),
+            Some(Status::Failed)
+        );
+    }
+
+    #[test]
+    fn running_nodes_are_halted() {
+        let mut tree = BehaviorTree::new(Invert::new(AlwaysRunning::new()));
+        let buffer = Buffer::default();
+        let recorder = Recorder::new(buffer.clone(), &tree).unwrap();
+        tree.add_observer(recorder);
+        tree.tick(&mut ());
+        tree.reset();
+        drop(tree);
+
+        let mut replay = Replay::parse(&buffer.text()).unwrap();
+        assert_eq!(replay.events().len(), 4);
+        assert_eq!(replay.events()[3].old, Some(Status::Running));
+        assert_eq!(replay.events()[3].new, None);
+        replay.step();
+        assert_eq!(replay.root().status(), None);
+        assert_eq!(replay.root().children()[0].status(), None);
+    }
+
+    #[test]
+    fn errors() {
+        let header = "{ \"type\": \"Invert\", \"children\": [{ \"type\": \"AlwaysFail\" }] }";
+        let err = |text: &str| match Replay::parse(text) {
+            Ok(_) => panic!("Trace should not have parsed"),
+            Err(e) => e.to_string(),
+        };
+
+        assert_eq!(err(""), "Trace is empty");
+        assert_eq!(
+            err("{ \"type\": 1 }"),
+            "1:11: Expected \"type\" to be a string, found a number"
+        );
+        assert_eq!(
+            err(&format!("{}\n\n{{ \"tick\": 1 ", header)),
+            "3:13: Expected ',' or '}'"
+        );
+        assert_eq!(
+            err(&format!(
+                "{}\n{{ \"tick\": 1, \"time\": 0, \"node\": 2, \"old\": null, \"new\": \"Failed\" }}",
+                header
+            )),
+            "2:1: Expected \"node\" to be the ID of a node in the tree"
+        );
+        assert_eq!(
+            err(&format!(
+                "{}\n{{ \"tick\": 1, \"time\": 0, \"node\": 1, \"old\": null, \"new\": \"Done\" }}",
+                header
+            )),
+            "2:1: Expected \"new\" to be a status or null"
+        );
+        assert_eq!(
+            err(&format!(
+                "{}\n{{ \"tick\": 1, \"time\": 1e300, \"node\": 1, \"old\": null, \"new\": \"Failed\" }}",
+                header
+            )),
+            "2:1: Expected \"time\" to be a non-negative number"
+        );
+    }
+}